@@ -5,34 +5,64 @@
  */
 
 use crate::{
-    arguments::{Arguments, Scene},
+    arguments::{Arguments, Integrator, Scene},
     camera::Camera,
+    density_fields::{perlin_density::PerlinDensity, voxel_grid::VoxelGrid, DensityField},
     hittable::{
+        alpha_mask::AlphaMask,
         bvh_node::BvhNode,
         constant_medium::ConstantMedium,
         cuboid::Cuboid,
+        flip_face::FlipFace,
+        heterogeneous_medium::HeterogeneousMedium,
+        medium_boundary::MediumBoundary,
         moving_sphere::MovingSphere,
         rect::{Plane, Rect},
         rotation::{Axis, Rotation},
         sphere::Sphere,
         translation::Translation,
+        triangle::Triangle,
         Hittable,
     },
+    integrators::{
+        metropolis::{Metropolis, MetropolisSettings},
+        path_tracer,
+        photon_mapper::PhotonMapper,
+        ProgressiveIntegrator,
+    },
+    lights::{
+        area_light::AreaLight, directional_light::DirectionalLight,
+        environment_light::EnvironmentLight, ies_profile::IesProfile, physical_sky::PhysicalSky,
+        point_light::PointLight, spot_light::SpotLight, Light,
+    },
     materials::{
-        dielectric::Dielectric, diffuse_light::DiffuseLight, lambertian::Lambertian, metal::Metal,
+        bump_map::BumpMap, conductor::Conductor, dielectric::Dielectric,
+        diffuse_light::DiffuseLight, henyey_greenstein::HenyeyGreenstein, isotropic::Isotropic,
+        lambertian::Lambertian, layered::Layered, measured::Measured, metal::Metal,
+        mix_material::MixMaterial, normal_map::NormalMap, oren_nayar::OrenNayar,
+        pass_through::PassThrough, principled::Principled, rough_dielectric::RoughDielectric,
+        thin_film::ThinFilm,
     },
     math::Vec3,
+    media::{
+        homogeneous_medium::HomogeneousMedium, subsurface_medium::SubsurfaceMedium, Medium,
+        MediumInterface,
+    },
+    microfacet::{Beckmann, Ggx},
     ray::Ray,
+    sampler,
     textures::{
         checker_texture::CheckerTexture, image_texture::ImageTexture, noise_texture::NoiseTexture,
         solid_color::SolidColor,
     },
+    world::World,
 };
 
-use cgmath::{ElementWise, InnerSpace, Vector2, Vector4};
+use cgmath::{InnerSpace, Vector2, Vector4};
 use glfw::{Action, Context, Glfw, Key, Window, WindowEvent};
 use rand::Rng;
 use std::{
+    f32::consts::PI,
     sync::{
         atomic::{AtomicU32, Ordering},
         mpsc::Receiver,
@@ -60,17 +90,22 @@ pub(crate) struct Application {
     screen_texture: u32,
     screen_framebuffer: u32,
 
-    background: Vec3,
+    integrator: Integrator,
+    spectral: bool,
     samples: u32,
     depth: u32,
+    photons: u32,
+    photon_radius: f32,
+    metropolis_settings: MetropolisSettings,
 
     camera: Camera,
-    world: Arc<Box<dyn Hittable>>,
+    world: Arc<World>,
 
     start_time: Instant,
     tile_size: u32,
     tile_x_count: u32,
     tile_y_count: u32,
+    tile_total: u32,
     tile_counter: Arc<AtomicU32>,
 
     tx: mpsc::Sender<Tile>,
@@ -126,7 +161,6 @@ impl Application {
         let look_at;
         let fov;
         let aperture;
-        let background;
 
         log::info!("Generating world...");
         let world = match arguments.scene {
@@ -135,7 +169,6 @@ impl Application {
                 look_at = Vec3::new(0.0, 0.0, 0.0);
                 fov = 20.0;
                 aperture = 0.1;
-                background = Vec3::new(0.7, 0.8, 1.0);
                 Self::generate_random_scene()
             }
             Scene::TwoSpheres => {
@@ -143,7 +176,6 @@ impl Application {
                 look_at = Vec3::new(0.0, 0.0, 0.0);
                 fov = 20.0;
                 aperture = 0.0;
-                background = Vec3::new(0.7, 0.8, 1.0);
                 Self::generate_two_spheres()
             }
             Scene::TwoPerlinSpheres => {
@@ -151,7 +183,6 @@ impl Application {
                 look_at = Vec3::new(0.0, 0.0, 0.0);
                 fov = 20.0;
                 aperture = 0.0;
-                background = Vec3::new(0.7, 0.8, 1.0);
                 Self::generate_two_perlin_spheres()
             }
             Scene::Earth => {
@@ -159,7 +190,6 @@ impl Application {
                 look_at = Vec3::new(0.0, 0.0, 0.0);
                 fov = 20.0;
                 aperture = 0.0;
-                background = Vec3::new(0.7, 0.8, 1.0);
                 Self::generate_earth()
             }
             Scene::SimpleLight => {
@@ -167,7 +197,6 @@ impl Application {
                 look_at = Vec3::new(0.0, 2.0, 0.0);
                 fov = 20.0;
                 aperture = 0.0;
-                background = Vec3::new(0.0, 0.0, 0.0);
                 Self::generate_simple_light()
             }
             Scene::Cornell => {
@@ -175,7 +204,6 @@ impl Application {
                 look_at = Vec3::new(278.0, 278.0, 0.0);
                 fov = 40.0;
                 aperture = 0.0;
-                background = Vec3::new(0.0, 0.0, 0.0);
                 Self::generate_cornell_box()
             }
             Scene::CornellSmoke => {
@@ -183,15 +211,185 @@ impl Application {
                 look_at = Vec3::new(278.0, 278.0, 0.0);
                 fov = 40.0;
                 aperture = 0.0;
-                background = Vec3::new(0.0, 0.0, 0.0);
                 Self::generate_cornell_smoke_box()
             }
+            Scene::CornellGlass => {
+                look_from = Vec3::new(278.0, 278.0, -800.0);
+                look_at = Vec3::new(278.0, 278.0, 0.0);
+                fov = 40.0;
+                aperture = 0.0;
+                Self::generate_cornell_glass_box()
+            }
+            Scene::CornellMedia => {
+                look_from = Vec3::new(278.0, 278.0, -800.0);
+                look_at = Vec3::new(278.0, 278.0, 0.0);
+                fov = 40.0;
+                aperture = 0.0;
+                Self::generate_cornell_media_box()
+            }
+            Scene::Dispersion => {
+                look_from = Vec3::new(0.0, 6.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_dispersion()
+            }
+            Scene::Metals => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_metals()
+            }
+            Scene::FrostedGlass => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_frosted_glass()
+            }
+            Scene::Principled => {
+                look_from = Vec3::new(0.0, 3.0, 16.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_principled()
+            }
+            Scene::Furnace => {
+                look_from = Vec3::new(0.0, 0.0, 16.0);
+                look_at = Vec3::new(0.0, 0.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_furnace()
+            }
+            Scene::RoughDiffuse => {
+                look_from = Vec3::new(0.0, 2.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_rough_diffuse()
+            }
+            Scene::Layered => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_layered()
+            }
+            Scene::MixMaterial => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_mix_material()
+            }
+            Scene::Iridescence => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_iridescence()
+            }
+            Scene::Subsurface => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_subsurface()
+            }
+            Scene::Bumps => {
+                look_from = Vec3::new(0.0, 4.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_bumps()
+            }
+            Scene::Cutout => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_cutout()
+            }
+            Scene::BrushedMetal => {
+                look_from = Vec3::new(0.0, 4.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_brushed_metal()
+            }
+            Scene::Measured => {
+                look_from = Vec3::new(0.0, 3.0, 10.0);
+                look_at = Vec3::new(0.0, 1.2, 0.0);
+                fov = 25.0;
+                aperture = 0.0;
+                Self::generate_measured(arguments.brdf.as_deref().unwrap_or_default())
+            }
+            Scene::Emitters => {
+                look_from = Vec3::new(0.0, 4.0, 16.0);
+                look_at = Vec3::new(0.0, 1.5, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_emitters()
+            }
+            Scene::DeltaLights => {
+                look_from = Vec3::new(0.0, 3.0, 14.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_delta_lights()
+            }
+            Scene::Environment => {
+                look_from = Vec3::new(0.0, 2.0, 12.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 30.0;
+                aperture = 0.0;
+                Self::generate_environment(&arguments.environment)
+            }
+            Scene::Sky => {
+                look_from = Vec3::new(-6.0, 4.0, 18.0);
+                look_at = Vec3::new(0.0, 2.0, 0.0);
+                fov = 40.0;
+                aperture = 0.0;
+                Self::generate_sky(
+                    arguments.sun_elevation,
+                    arguments.sun_azimuth,
+                    arguments.turbidity,
+                )
+            }
+            Scene::Ies => {
+                look_from = Vec3::new(0.0, 3.5, 12.0);
+                look_at = Vec3::new(0.0, 3.0, -3.0);
+                fov = 40.0;
+                aperture = 0.0;
+                Self::generate_ies(&arguments.ies)
+            }
+            Scene::ManyLights => {
+                look_from = Vec3::new(0.0, 6.0, 22.0);
+                look_at = Vec3::new(0.0, 1.0, 0.0);
+                fov = 35.0;
+                aperture = 0.0;
+                Self::generate_many_lights()
+            }
+            Scene::EmissiveShapes => {
+                look_from = Vec3::new(0.0, 4.0, 16.0);
+                look_at = Vec3::new(0.0, 1.5, 0.0);
+                fov = 35.0;
+                aperture = 0.0;
+                Self::generate_emissive_shapes()
+            }
+            Scene::Clouds => {
+                look_from = Vec3::new(13.0, 3.0, 3.0);
+                look_at = Vec3::new(0.0, 2.5, 0.0);
+                fov = 35.0;
+                aperture = 0.0;
+                Self::generate_clouds()
+            }
             Scene::Final => {
                 look_from = Vec3::new(478.0, 278.0, -600.0);
                 look_at = Vec3::new(278.0, 278.0, 0.0);
                 fov = 40.0;
                 aperture = 0.0;
-                background = Vec3::new(0.0, 0.0, 0.0);
                 Self::generate_final_scene()
             }
         };
@@ -220,9 +418,18 @@ impl Application {
             texture_size: Vector2::new(0, 0),
             screen_texture,
             screen_framebuffer,
-            background,
+            integrator: arguments.integrator,
+            spectral: arguments.spectral,
             samples: arguments.samples,
             depth: arguments.depth,
+            photons: arguments.photons,
+            photon_radius: arguments.photon_radius,
+            metropolis_settings: MetropolisSettings {
+                bootstrap_samples: arguments.bootstrap_samples,
+                chains: arguments.chains,
+                large_step_probability: arguments.large_step_probability,
+                small_step_sigma: arguments.small_step_sigma,
+            },
 
             camera,
             world: Arc::new(world),
@@ -231,6 +438,7 @@ impl Application {
             tile_size: arguments.tile_size,
             tile_x_count: 0,
             tile_y_count: 0,
+            tile_total: 0,
             tile_counter: Arc::default(),
 
             tx,
@@ -257,7 +465,7 @@ impl Application {
                 delta_time.as_secs_f32(),
             ));
 
-            if self.tile_counter.load(Ordering::SeqCst) == self.tile_x_count * self.tile_y_count {
+            if self.tile_counter.load(Ordering::SeqCst) == self.tile_total {
                 let duration = self.start_time.elapsed();
 
                 let seconds = duration.as_secs() % 60;
@@ -274,7 +482,7 @@ impl Application {
                 log::info!("  Height: {}", self.texture_size.y);
                 log::info!("  Samples: {}", self.samples);
                 log::info!("  Depth: {}", self.depth);
-                log::info!("  Objects: {}", self.world.count());
+                log::info!("  Objects: {}", self.world.objects().count());
 
                 self.tile_counter.store(0, Ordering::SeqCst);
             }
@@ -391,17 +599,60 @@ impl Application {
     }
 
     fn render(&mut self) {
+        match self.integrator {
+            Integrator::PathTracer => self.render_tiles(),
+            Integrator::PhotonMapper => {
+                if self.spectral {
+                    log::warn!("Spectral rendering isn't supported by the photon mapper");
+                }
+
+                let photon_mapper = PhotonMapper::new(
+                    self.camera.clone(),
+                    self.world.clone(),
+                    self.texture_size.x as u32,
+                    self.texture_size.y as u32,
+                    self.depth,
+                    self.photons,
+                    self.photon_radius * self.camera.pixel_footprint(self.texture_size.y),
+                );
+                self.render_progressive(photon_mapper);
+            }
+            Integrator::Metropolis => {
+                let metropolis = Metropolis::new(
+                    self.camera.clone(),
+                    self.world.clone(),
+                    self.texture_size.x as u32,
+                    self.texture_size.y as u32,
+                    self.depth,
+                    self.metropolis_settings,
+                    self.ray_color(),
+                );
+                self.render_progressive(metropolis);
+            }
+        }
+    }
+
+    fn ray_color(&self) -> fn(&Ray, &World, u32) -> Vec3 {
+        if self.spectral {
+            path_tracer::spectral_ray_color
+        } else {
+            path_tracer::ray_color
+        }
+    }
+
+    fn render_tiles(&mut self) {
+        let ray_color = self.ray_color();
         let width = self.texture_size.x as usize;
         let height = self.texture_size.y as usize;
         let sample_count = self.samples;
-        let background = self.background;
         let depth = self.depth;
         let tile_size = self.tile_size;
         let tile_x_count = self.tile_x_count;
         let tile_y_count = self.tile_y_count;
 
         let scale = 1.0 / sample_count as f32;
-        for i in 0..(self.tile_x_count * self.tile_y_count) {
+        self.tile_total = self.tile_x_count * self.tile_y_count;
+        for i in 0..self.tile_total {
             let x = i % self.tile_x_count;
             let y = i / self.tile_x_count;
 
@@ -433,7 +684,7 @@ impl Application {
                     vec![Vector4::new(0.0, 0.0, 0.0, 0.0); (tile_width * tile_height) as usize];
                 // TODO: Handle edge cases of screen which are not / 40
                 {
-                    let mut rand = rand::thread_rng();
+                    let mut rand = sampler::rng();
                     for i in 0..(tile_width * tile_height) {
                         let x = (i % tile_width) + local_x;
                         let y = (i / tile_width) + local_y;
@@ -445,15 +696,11 @@ impl Application {
                             let v = (y as f32 + rand.gen::<f32>()) / (height as f32 - 1.0);
 
                             let ray = camera.get_ray(u, v);
-                            pixel_color += Self::ray_color(&ray, background, &**world, depth);
+                            pixel_color += ray_color(&ray, &world, depth);
                         }
 
-                        pixel_color.x = (pixel_color.x * scale).sqrt();
-                        pixel_color.y = (pixel_color.y * scale).sqrt();
-                        pixel_color.z = (pixel_color.z * scale).sqrt();
-
                         local_pixels[((x - local_x) + tile_width * (y - local_y)) as usize] =
-                            Vector4::new(pixel_color.x, pixel_color.y, pixel_color.z, 1.0);
+                            Self::gamma_correct(pixel_color * scale);
                     }
                 }
 
@@ -474,27 +721,45 @@ impl Application {
         }
     }
 
-    fn ray_color(ray: &Ray, background: Vec3, world: &dyn Hittable, depth: u32) -> Vec3 {
-        if depth == 0 {
-            return Vec3::new(0.0, 0.0, 0.0);
-        }
+    fn render_progressive(&mut self, mut integrator: impl ProgressiveIntegrator + 'static) {
+        let width = self.texture_size.x as u32;
+        let height = self.texture_size.y as u32;
+        let passes = self.samples;
+        let tx = self.tx.clone();
+        let tile_counter = self.tile_counter.clone();
 
-        let Some(hit_record) = world.hit(ray, 0.001, f32::INFINITY) else {
-            return background
-        };
+        self.tile_total = 1;
+        self.tasks.push(tokio::spawn(async move {
+            for _ in 0..passes {
+                integrator.render_pass();
 
-        let emitted = hit_record
-            .material
-            .emitted(hit_record.u, hit_record.v, hit_record.point);
-        let Some((attenuation, scattered)) = hit_record.material.scatter(ray, &hit_record) else {
-            return emitted
-        };
+                let tile = Tile {
+                    x: 0,
+                    y: 0,
+                    width,
+                    height,
+                    pixels: integrator
+                        .image()
+                        .into_iter()
+                        .map(Self::gamma_correct)
+                        .collect(),
+                };
+
+                tx.send(tile).await.unwrap();
+            }
 
-        let ray_color = Self::ray_color(&scattered, background, world, depth - 1);
-        attenuation.mul_element_wise(ray_color) + emitted
+            let counter = tile_counter.load(Ordering::SeqCst);
+            tile_counter.store(counter + 1, Ordering::SeqCst);
+        }));
     }
 
-    fn generate_random_scene() -> Box<dyn Hittable> {
+    fn gamma_correct(color: Vec3) -> Vector4<f32> {
+        // Spectral samples outside of the sRGB gamut can end up slightly negative
+        let color = color.map(|channel| channel.max(0.0));
+        Vector4::new(color.x.sqrt(), color.y.sqrt(), color.z.sqrt(), 1.0)
+    }
+
+    fn generate_random_scene() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
 
         objects.push(Box::new(Sphere::new(
@@ -561,10 +826,14 @@ impl Application {
             Metal::new(Vec3::new(0.7, 0.6, 0.5), 0.0),
         )));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
     }
 
-    fn generate_two_spheres() -> Box<dyn Hittable> {
+    fn generate_two_spheres() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
 
         let checker = Lambertian::new(CheckerTexture::new(
@@ -583,10 +852,14 @@ impl Application {
             checker,
         )));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
     }
 
-    fn generate_two_perlin_spheres() -> Box<dyn Hittable> {
+    fn generate_two_perlin_spheres() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
 
         let noise = Lambertian::new(NoiseTexture::new(4.0));
@@ -598,20 +871,28 @@ impl Application {
         )));
         objects.push(Box::new(Sphere::new(Vec3::new(0.0, 2.0, 0.0), 2.0, noise)));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
     }
 
-    fn generate_earth() -> Box<dyn Hittable> {
+    fn generate_earth() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
 
         let earth = Lambertian::new(ImageTexture::new("./assets/earthmap.jpg"));
 
         objects.push(Box::new(Sphere::new(Vec3::new(0.0, 0.0, 0.0), 2.0, earth)));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
     }
 
-    fn generate_simple_light() -> Box<dyn Hittable> {
+    fn generate_simple_light() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
 
         let noise = Lambertian::new(NoiseTexture::new(4.0));
@@ -623,22 +904,19 @@ impl Application {
         objects.push(Box::new(Sphere::new(Vec3::new(0.0, 2.0, 0.0), 2.0, noise)));
 
         let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(4.0, 4.0, 4.0)));
-        objects.push(Box::new(Rect::new(
-            Plane::XY,
-            3.0,
-            5.0,
-            1.0,
-            3.0,
-            -2.0,
-            diffuse_light,
-        )));
+        let light = Rect::new(Plane::XY, 3.0, 5.0, 1.0, 3.0, -2.0, diffuse_light);
+        objects.push(Box::new(light.clone()));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
-    }
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
 
-    fn generate_cornell_box() -> Box<dyn Hittable> {
-        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
 
+    fn generate_cornell_walls(objects: &mut Vec<Box<dyn Hittable>>) -> Vec<Box<dyn Light>> {
         let red = Lambertian::new(SolidColor::new(Vec3::new(0.65, 0.05, 0.05)));
         let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
         let green = Lambertian::new(SolidColor::new(Vec3::new(0.12, 0.45, 0.15)));
@@ -662,7 +940,7 @@ impl Application {
             0.0,
             red,
         )));
-        objects.push(Box::new(Rect::new(
+        let light = FlipFace::new(Rect::new(
             Plane::ZX,
             213.0,
             343.0,
@@ -670,7 +948,8 @@ impl Application {
             332.0,
             554.0,
             light,
-        )));
+        ));
+        objects.push(Box::new(light.clone()));
         objects.push(Box::new(Rect::new(
             Plane::ZX,
             0.0,
@@ -696,9 +975,18 @@ impl Application {
             0.0,
             555.0,
             555.0,
-            white.clone(),
+            white,
         )));
 
+        vec![Box::new(AreaLight::new(Box::new(light)))]
+    }
+
+    fn generate_cornell_box() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        let lights = Self::generate_cornell_walls(&mut objects);
+
+        let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
+
         let mut cuboid_1: Box<dyn Hittable> = Box::new(Cuboid::new(
             Vec3::new(0.0, 0.0, 0.0),
             Vec3::new(165.0, 330.0, 165.0),
@@ -717,71 +1005,18 @@ impl Application {
         cuboid_2 = Box::new(Translation::new(cuboid_2, Vec3::new(130.0, 0.0, 65.0)));
         objects.push(cuboid_2);
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
     }
 
-    fn generate_cornell_smoke_box() -> Box<dyn Hittable> {
+    fn generate_cornell_smoke_box() -> World {
         let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        let lights = Self::generate_cornell_walls(&mut objects);
 
-        let red = Lambertian::new(SolidColor::new(Vec3::new(0.65, 0.05, 0.05)));
         let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
-        let green = Lambertian::new(SolidColor::new(Vec3::new(0.12, 0.45, 0.15)));
-        let light = DiffuseLight::new(SolidColor::new(Vec3::new(15.0, 15.0, 15.0)));
-
-        objects.push(Box::new(Rect::new(
-            Plane::YZ,
-            0.0,
-            555.0,
-            0.0,
-            555.0,
-            555.0,
-            green,
-        )));
-        objects.push(Box::new(Rect::new(
-            Plane::YZ,
-            0.0,
-            555.0,
-            0.0,
-            555.0,
-            0.0,
-            red,
-        )));
-        objects.push(Box::new(Rect::new(
-            Plane::ZX,
-            213.0,
-            343.0,
-            227.0,
-            332.0,
-            554.0,
-            light,
-        )));
-        objects.push(Box::new(Rect::new(
-            Plane::ZX,
-            0.0,
-            555.0,
-            0.0,
-            555.0,
-            0.0,
-            white.clone(),
-        )));
-        objects.push(Box::new(Rect::new(
-            Plane::ZX,
-            0.0,
-            555.0,
-            0.0,
-            555.0,
-            555.0,
-            white.clone(),
-        )));
-        objects.push(Box::new(Rect::new(
-            Plane::XY,
-            0.0,
-            555.0,
-            0.0,
-            555.0,
-            555.0,
-            white.clone(),
-        )));
 
         let mut cuboid_1: Box<dyn Hittable> = Box::new(Cuboid::new(
             Vec3::new(0.0, 0.0, 0.0),
@@ -793,7 +1028,7 @@ impl Application {
         cuboid_1 = Box::new(ConstantMedium::new(
             cuboid_1,
             0.01,
-            SolidColor::new(Vec3::new(0.0, 0.0, 0.0)),
+            Isotropic::new(SolidColor::new(Vec3::new(0.0, 0.0, 0.0))),
         ));
         objects.push(cuboid_1);
 
@@ -807,91 +1042,1326 @@ impl Application {
         cuboid_2 = Box::new(ConstantMedium::new(
             cuboid_2,
             0.01,
-            SolidColor::new(Vec3::new(1.0, 1.0, 1.0)),
+            Isotropic::new(SolidColor::new(Vec3::new(1.0, 1.0, 1.0))),
         ));
         objects.push(cuboid_2);
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
     }
 
-    fn generate_final_scene() -> Box<dyn Hittable> {
-        const BOXES_PER_SIDE: usize = 20;
+    fn generate_cornell_glass_box() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        let lights = Self::generate_cornell_walls(&mut objects);
 
-        let mut rand = rand::thread_rng();
+        let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
 
-        let ground_material = Lambertian::new(SolidColor::new(Vec3::new(0.48, 0.83, 0.53)));
-        let mut ground_boxes: Vec<Box<dyn Hittable>> = Vec::new();
-        for i in 0..BOXES_PER_SIDE {
-            for j in 0..BOXES_PER_SIDE {
-                let w = 100.0;
-                let x0 = -1000.0 + i as f32 * w;
-                let z0 = -1000.0 + j as f32 * w;
-                let y0 = 0.0;
-                let x1 = x0 + w;
-                let y1 = rand.gen_range(1.0..101.0);
-                let z1 = z0 + w;
+        let mut cuboid: Box<dyn Hittable> = Box::new(Cuboid::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(165.0, 330.0, 165.0),
+            white,
+        ));
+        cuboid = Box::new(Rotation::new(Axis::Y, cuboid, 15.0));
+        cuboid = Box::new(Translation::new(cuboid, Vec3::new(265.0, 0.0, 295.0)));
+        objects.push(cuboid);
 
-                ground_boxes.push(Box::new(Cuboid::new(
-                    Vec3::new(x0, y0, z0),
-                    Vec3::new(x1, y1, z1),
-                    ground_material.clone(),
-                )));
-            }
-        }
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(190.0, 90.0, 190.0),
+            90.0,
+            Dielectric::new(1.5),
+        )));
 
-        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
 
-        objects.push(Box::new(BvhNode::new(ground_boxes, 0.0, 1.0)));
+    fn generate_cornell_media_box() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+        let lights = Self::generate_cornell_walls(&mut objects);
 
-        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(7.0, 7.0, 7.0)));
-        objects.push(Box::new(Rect::new(
-            Plane::ZX,
-            123.0,
-            423.0,
-            147.0,
-            412.0,
-            554.0,
-            diffuse_light,
+        // Thin fog filling the box, the other volumes are nested inside of it
+        let fog: Arc<dyn Medium> = Arc::new(HomogeneousMedium::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            0.0015,
+            HenyeyGreenstein::new(SolidColor::new(Vec3::new(1.0, 1.0, 1.0)), 0.5),
+        ));
+        objects.push(Box::new(MediumBoundary::new(
+            Box::new(Cuboid::new(
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(554.0, 553.0, 554.0),
+                PassThrough::new(),
+            )),
+            MediumInterface::new(Some(fog.clone()), None),
         )));
 
-        let center_1 = Vec3::new(400.0, 400.0, 200.0);
-        let center_2 = center_1 + Vec3::new(30.0, 0.0, 0.0);
+        let murky_water: Arc<dyn Medium> = Arc::new(HomogeneousMedium::new(
+            Vec3::new(0.004, 0.0015, 0.001),
+            0.01,
+            HenyeyGreenstein::new(SolidColor::new(Vec3::new(0.8, 0.9, 0.8)), 0.7),
+        ));
+        let mut water: Box<dyn Hittable> = Box::new(Cuboid::new(
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(165.0, 330.0, 165.0),
+            Dielectric::new(1.33),
+        ));
+        water = Box::new(Rotation::new(Axis::Y, water, 15.0));
+        water = Box::new(Translation::new(water, Vec3::new(265.0, 2.0, 295.0)));
+        objects.push(Box::new(MediumBoundary::new(
+            water,
+            MediumInterface::new(Some(murky_water), Some(fog.clone())),
+        )));
 
-        let moving_sphere_material = Lambertian::new(SolidColor::new(Vec3::new(0.7, 0.3, 0.1)));
-        objects.push(Box::new(MovingSphere::new(
-            center_1,
-            center_2,
+        let green_glass: Arc<dyn Medium> = Arc::new(HomogeneousMedium::new(
+            Vec3::new(0.012, 0.001, 0.008),
             0.0,
-            1.0,
-            50.0,
-            moving_sphere_material,
+            Isotropic::new(SolidColor::new(Vec3::new(1.0, 1.0, 1.0))),
+        ));
+        objects.push(Box::new(MediumBoundary::new(
+            Box::new(Sphere::new(
+                Vec3::new(190.0, 92.0, 190.0),
+                90.0,
+                Dielectric::new(1.5),
+            )),
+            MediumInterface::new(Some(green_glass), Some(fog)),
         )));
 
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_dispersion() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
         objects.push(Box::new(Sphere::new(
-            Vec3::new(260.0, 150.0, 45.0),
-            50.0,
-            Dielectric::new(1.5),
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            white,
         )));
 
+        // Dense flint glass (SF11)
         objects.push(Box::new(Sphere::new(
-            Vec3::new(0.0, 150.0, 145.0),
-            50.0,
-            Metal::new(Vec3::new(0.8, 0.8, 0.9), 1.0),
+            Vec3::new(-1.6, 1.2, 0.0),
+            1.2,
+            Dielectric::sellmeier(
+                [1.737_597, 0.313_747, 1.898_781],
+                [0.013_188_7, 0.062_306_8, 155.236_3],
+            ),
         )));
 
-        let boundary = Sphere::new(Vec3::new(360.0, 150.0, 145.0), 70.0, Dielectric::new(1.5));
-        objects.push(Box::new(boundary.clone()));
-        objects.push(Box::new(ConstantMedium::new(
-            Box::new(boundary),
-            0.2,
-            SolidColor::new(Vec3::new(0.2, 0.4, 0.9)),
-        )));
+        let mut diamond: Box<dyn Hittable> = Box::new(Cuboid::new(
+            Vec3::new(-0.9, 0.0, -0.9),
+            Vec3::new(0.9, 1.8, 0.9),
+            Dielectric::cauchy(2.38, 0.0117),
+        ));
+        diamond = Box::new(Rotation::new(Axis::Y, diamond, 35.0));
+        diamond = Box::new(Translation::new(diamond, Vec3::new(1.6, 0.0, 0.0)));
+        objects.push(diamond);
+
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(60.0, 60.0, 60.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::ZX,
+            -0.4,
+            0.4,
+            -0.4,
+            0.4,
+            7.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_metals() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        let roughness = |value| SolidColor::new(Vec3::new(value, value, value));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-3.3, 1.0, 0.0),
+            1.0,
+            Conductor::gold(roughness(0.2)),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-1.1, 1.0, 0.0),
+            1.0,
+            Conductor::copper(roughness(0.4)),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.1, 1.0, 0.0),
+            1.0,
+            Conductor::silver(roughness(0.0)),
+        )));
+
+        // Aluminium with a marbled roughness, alternating between polished and matte
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(3.3, 1.0, 0.0),
+            1.0,
+            Conductor::aluminium(NoiseTexture::new(4.0)),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_frosted_glass() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        // Colored spheres behind the glass show how much each surface blurs
+        for i in 0..7 {
+            let hue = i as f32 / 7.0;
+            let color = Vec3::new(
+                0.5 + 0.5 * (2.0 * PI * hue).cos(),
+                0.5 + 0.5 * (2.0 * PI * (hue - 1.0 / 3.0)).cos(),
+                0.5 + 0.5 * (2.0 * PI * (hue - 2.0 / 3.0)).cos(),
+            );
+            objects.push(Box::new(Sphere::new(
+                Vec3::new(-4.5 + 1.5 * i as f32, 0.5, -3.0),
+                0.5,
+                Lambertian::new(SolidColor::new(color)),
+            )));
+        }
+
+        let roughness = |value| SolidColor::new(Vec3::new(value, value, value));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.2, 1.0, 0.0),
+            1.0,
+            RoughDielectric::<Ggx, _>::new(1.5, roughness(0.1)),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            RoughDielectric::<Beckmann, _>::new(1.5, roughness(0.3)),
+        )));
+
+        // Frosted checker pattern on otherwise clear glass
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(2.2, 1.0, 0.0),
+            1.0,
+            RoughDielectric::<Ggx, _>::new(
+                1.5,
+                CheckerTexture::new(roughness(0.0), roughness(0.35)),
+            ),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_principled() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        let constant = |value| SolidColor::new(Vec3::new(value, value, value));
+        let materials = [
+            Principled::new(SolidColor::new(Vec3::new(0.8, 0.1, 0.1))),
+            Principled::new(SolidColor::new(Vec3::new(1.0, 0.78, 0.34)))
+                .with_metallic(constant(1.0))
+                .with_roughness(constant(0.3)),
+            Principled::new(SolidColor::new(Vec3::new(0.05, 0.1, 0.6)))
+                .with_roughness(constant(0.6))
+                .with_clearcoat(constant(1.0)),
+            Principled::new(SolidColor::new(Vec3::new(0.3, 0.02, 0.05)))
+                .with_roughness(constant(1.0))
+                .with_sheen(constant(1.0)),
+            Principled::new(SolidColor::new(Vec3::new(0.8, 1.0, 0.85)))
+                .with_roughness(constant(0.05))
+                .with_transmission(constant(1.0)),
+            Principled::new(SolidColor::new(Vec3::new(0.2, 0.2, 0.2)))
+                .with_emission(SolidColor::new(Vec3::new(4.0, 1.6, 0.4))),
+        ];
+
+        for (i, material) in materials.into_iter().enumerate() {
+            objects.push(Box::new(Sphere::new(
+                Vec3::new(-5.5 + 2.2 * i as f32, 1.0, 0.0),
+                1.0,
+                material,
+            )));
+        }
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    /// White furnace, every material lit by a uniform white environment. Nothing that
+    /// doesn't emit light may end up brighter than the background.
+    fn generate_furnace() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let constant = |value| SolidColor::new(Vec3::new(value, value, value));
+        let materials = [
+            Principled::new(constant(1.0)),
+            Principled::new(constant(1.0)).with_metallic(constant(1.0)),
+            Principled::new(constant(1.0)).with_specular(constant(1.0)),
+            Principled::new(constant(1.0)).with_sheen(constant(1.0)),
+            Principled::new(constant(1.0))
+                .with_specular(constant(1.0))
+                .with_clearcoat(constant(1.0)),
+            Principled::new(constant(1.0)).with_transmission(constant(1.0)),
+        ];
+
+        for (i, material) in materials.into_iter().enumerate() {
+            let roughness = [0.0, 0.5, 1.0];
+            for (j, roughness) in roughness.into_iter().enumerate() {
+                objects.push(Box::new(Sphere::new(
+                    Vec3::new(-5.5 + 2.2 * i as f32, 2.2 - 2.2 * j as f32, 0.0),
+                    1.0,
+                    material.clone().with_roughness(constant(roughness)),
+                )));
+            }
+        }
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(1.0, 1.0, 1.0),
+        )
+    }
+
+    fn generate_rough_diffuse() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            OrenNayar::new(SolidColor::new(Vec3::new(0.5, 0.5, 0.48)), 30.0),
+        )));
+
+        let clay = SolidColor::new(Vec3::new(0.7, 0.4, 0.3));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-1.6, 1.2, 0.0),
+            1.2,
+            Lambertian::new(clay.clone()),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.6, 1.2, 0.0),
+            1.2,
+            OrenNayar::new(clay, 40.0),
+        )));
+
+        // Lit from behind the camera, where rough surfaces flatten out like the moon
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(10.0, 10.0, 10.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::XY,
+            -3.0,
+            3.0,
+            3.0,
+            7.0,
+            16.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_layered() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        let roughness = |value| SolidColor::new(Vec3::new(value, value, value));
+
+        // Car paint, metallic flakes under a glossy clear coat
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-3.3, 1.0, 0.0),
+            1.0,
+            Layered::new(
+                Conductor::new(
+                    Vec3::new(1.2, 0.4, 0.3),
+                    Vec3::new(3.5, 1.2, 1.0),
+                    roughness(0.5),
+                ),
+                1.5,
+                roughness(0.0),
+            ),
+        )));
+
+        // Varnished wood, the varnish tints the grain in amber
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-1.1, 1.0, 0.0),
+            1.0,
+            Layered::new(
+                Lambertian::new(NoiseTexture::new(2.0)),
+                1.5,
+                roughness(0.15),
+            )
+            .with_absorption(Vec3::new(0.1, 0.4, 1.2), 1.0),
+        )));
+
+        // Ceramic, white glaze over a diffuse body
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.1, 1.0, 0.0),
+            1.0,
+            Layered::new(
+                Lambertian::new(SolidColor::new(Vec3::new(0.9, 0.88, 0.85))),
+                1.5,
+                roughness(0.0),
+            ),
+        )));
+
+        // Lacquered metal with a frosted coat
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(3.3, 1.0, 0.0),
+            1.0,
+            Layered::new(
+                Metal::new(Vec3::new(0.8, 0.8, 0.85), 0.0),
+                1.5,
+                roughness(0.3),
+            ),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_mix_material() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        let rust = OrenNayar::new(SolidColor::new(Vec3::new(0.45, 0.18, 0.07)), 30.0);
+
+        // Rust eating into polished steel where the noise is bright
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-1.6, 1.2, 0.0),
+            1.2,
+            MixMaterial::new(
+                Metal::new(Vec3::new(0.75, 0.75, 0.78), 0.05),
+                rust.clone(),
+                NoiseTexture::new(3.0),
+            ),
+        )));
+
+        // Gold covered in rust wherever the image mask is bright
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.6, 1.2, 0.0),
+            1.2,
+            MixMaterial::new(
+                Conductor::gold(SolidColor::new(Vec3::new(0.2, 0.2, 0.2))),
+                rust,
+                ImageTexture::new("./assets/earthmap.jpg"),
+            ),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_iridescence() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        // Soap bubbles are a film of water with air on both sides
+        let bubble = ThinFilm::dielectric(1.0, 1.33, NoiseTexture::new(1.5), 900.0);
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.4, 1.4, 0.0),
+            1.2,
+            bubble.clone(),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-0.8, 2.6, -1.5),
+            0.7,
+            bubble,
+        )));
+
+        // Heat tinted metal covered by a layer of oxide
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.8, 1.2, 0.0),
+            1.2,
+            ThinFilm::metal(
+                Vec3::new(2.54, 2.43, 2.2),
+                Vec3::new(3.43, 3.36, 3.12),
+                2.3,
+                NoiseTexture::new(0.8),
+                350.0,
+            ),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_subsurface() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(SolidColor::new(Vec3::new(0.5, 0.5, 0.5))),
+        )));
+
+        // The random walk takes one bounce per scattering event, render with a larger depth
+        let materials = [
+            // Marble
+            (
+                Vec3::new(0.3, 0.3, 0.3),
+                Vec3::new(0.999, 0.998, 0.995),
+                0.0,
+            ),
+            // Skin
+            (Vec3::new(0.4, 0.15, 0.08), Vec3::new(0.995, 0.95, 0.9), 0.0),
+            // Jade
+            (Vec3::new(0.15, 0.4, 0.2), Vec3::new(0.95, 0.99, 0.96), 0.3),
+        ];
+
+        for (i, (mean_free_path, albedo, anisotropy)) in materials.into_iter().enumerate() {
+            let medium: Arc<dyn Medium> = Arc::new(SubsurfaceMedium::new(
+                mean_free_path,
+                HenyeyGreenstein::new(SolidColor::new(albedo), anisotropy),
+            ));
+            objects.push(Box::new(MediumBoundary::new(
+                Box::new(Sphere::new(
+                    Vec3::new(-2.6 + 2.6 * i as f32, 1.0, 0.0),
+                    1.0,
+                    Dielectric::new(1.4),
+                )),
+                MediumInterface::new(Some(medium), None),
+            )));
+        }
+
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(6.0, 6.0, 6.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::ZX,
+            -3.0,
+            3.0,
+            -3.0,
+            3.0,
+            7.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.02, 0.02, 0.03),
+        )
+    }
+
+    fn generate_bumps() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let tiles = NormalMap::new(
+            Lambertian::new(SolidColor::new(Vec3::new(0.6, 0.6, 0.55))),
+            ImageTexture::new("./assets/tiles_normal.png"),
+        );
+        let corners = [
+            Vec3::new(-6.0, 0.0, 6.0),
+            Vec3::new(6.0, 0.0, 6.0),
+            Vec3::new(6.0, 0.0, -6.0),
+            Vec3::new(-6.0, 0.0, -6.0),
+        ];
+        objects.push(Box::new(
+            Triangle::new(corners[0], corners[1], corners[2], tiles.clone()).with_uvs([
+                (0.0, 0.0),
+                (1.0, 0.0),
+                (1.0, 1.0),
+            ]),
+        ));
+        objects.push(Box::new(
+            Triangle::new(corners[0], corners[2], corners[3], tiles).with_uvs([
+                (0.0, 0.0),
+                (1.0, 1.0),
+                (0.0, 1.0),
+            ]),
+        ));
+
+        let earth = ImageTexture::new("./assets/earthmap.jpg");
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.6, 1.0, 0.0),
+            1.0,
+            BumpMap::new(Lambertian::new(earth.clone()), earth, 0.01),
+        )));
+
+        // Hammered metal
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            BumpMap::new(
+                Metal::new(Vec3::new(0.8, 0.8, 0.85), 0.0),
+                NoiseTexture::new(4.0),
+                0.03,
+            ),
+        )));
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(2.6, 1.0, 0.0),
+            1.0,
+            NormalMap::new(
+                Lambertian::new(SolidColor::new(Vec3::new(0.8, 0.4, 0.2))),
+                ImageTexture::new("./assets/tiles_normal.png"),
+            ),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_cutout() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.3, 0.1)),
+            SolidColor::new(Vec3::new(0.9, 0.9, 0.9)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        // Fence with holes cut by a checker pattern
+        let holes = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.0, 0.0, 0.0)),
+            SolidColor::new(Vec3::new(1.0, 1.0, 1.0)),
+        );
+        objects.push(Box::new(AlphaMask::new(
+            Box::new(Rect::new(
+                Plane::XY,
+                -4.0,
+                4.0,
+                0.0,
+                2.5,
+                2.0,
+                Lambertian::new(SolidColor::new(Vec3::new(0.6, 0.4, 0.2))),
+            )),
+            holes,
+            0.5,
+        )));
+
+        // Only the continents of the earth remain
+        let earth = ImageTexture::new("./assets/earthmap.jpg");
+        objects.push(Box::new(AlphaMask::new(
+            Box::new(Sphere::new(
+                Vec3::new(-1.6, 1.2, -1.0),
+                1.2,
+                Lambertian::new(earth.clone()),
+            )),
+            earth,
+            0.2,
+        )));
+
+        // Stochastic opacity lets the sphere dissolve into noise
+        objects.push(Box::new(AlphaMask::stochastic(
+            Box::new(Sphere::new(
+                Vec3::new(1.6, 1.2, -1.0),
+                1.2,
+                Lambertian::new(SolidColor::new(Vec3::new(0.2, 0.4, 0.8))),
+            )),
+            NoiseTexture::new(2.0),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_brushed_metal() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let ground = Lambertian::new(SolidColor::new(Vec3::new(0.3, 0.3, 0.3)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground,
+        )));
+
+        // Brushed along the tangent of the sphere, which runs around its poles
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.6, 1.0, 0.0),
+            1.0,
+            Conductor::aluminium(SolidColor::new(Vec3::new(0.05, 0.05, 0.05)))
+                .with_bitangent_roughness(SolidColor::new(Vec3::new(0.5, 0.5, 0.5))),
+        )));
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            Conductor::aluminium(SolidColor::new(Vec3::new(0.5, 0.5, 0.5)))
+                .with_bitangent_roughness(SolidColor::new(Vec3::new(0.05, 0.05, 0.05))),
+        )));
+
+        // Swirling brush strokes
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(2.6, 1.0, 0.0),
+            1.0,
+            Conductor::copper(SolidColor::new(Vec3::new(0.05, 0.05, 0.05)))
+                .with_bitangent_roughness(SolidColor::new(Vec3::new(0.4, 0.4, 0.4)))
+                .with_rotation(NoiseTexture::new(1.0)),
+        )));
+
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(8.0, 8.0, 8.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::ZX,
+            -3.0,
+            3.0,
+            -3.0,
+            3.0,
+            6.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.05, 0.05, 0.06),
+        )
+    }
+
+    fn generate_measured(path: &str) -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.2, 0.2)),
+            SolidColor::new(Vec3::new(0.8, 0.8, 0.8)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        let center = Vec3::new(0.0, 1.2, 0.0);
+        match Measured::load(path) {
+            Ok(measured) => objects.push(Box::new(Sphere::new(center, 1.2, measured))),
+            Err(error) => {
+                log::error!("Failed to load measured BRDF '{}': {}", path, error);
+                objects.push(Box::new(Sphere::new(
+                    center,
+                    1.2,
+                    Lambertian::new(SolidColor::new(Vec3::new(1.0, 0.0, 1.0))),
+                )));
+            }
+        }
+
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(6.0, 6.0, 6.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::XY,
+            -2.0,
+            2.0,
+            3.0,
+            6.0,
+            4.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.3, 0.35, 0.4),
+        )
+    }
+
+    fn generate_emitters() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            white.clone(),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.5, 1.0, 0.0),
+            1.0,
+            white.clone(),
+        )));
+        objects.push(Box::new(Sphere::new(Vec3::new(2.5, 1.0, 0.0), 1.0, white)));
+
+        // Panel between the spheres lighting both of them
+        let panel = Rect::new(
+            Plane::YZ,
+            0.5,
+            2.5,
+            -1.0,
+            1.0,
+            0.0,
+            DiffuseLight::new(SolidColor::new(Vec3::new(4.0, 4.0, 4.0))).two_sided(),
+        );
+        objects.push(Box::new(panel.clone()));
+
+        // Spot light shining down from above
+        let spot = FlipFace::new(Rect::new(
+            Plane::ZX,
+            -0.5,
+            0.5,
+            -0.5,
+            0.5,
+            6.0,
+            DiffuseLight::new(SolidColor::new(Vec3::new(40.0, 32.0, 20.0))).with_cosine_power(20.0),
+        ));
+        objects.push(Box::new(spot.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![
+            Box::new(AreaLight::new(Box::new(panel))),
+            Box::new(AreaLight::new(Box::new(spot))),
+        ];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_delta_lights() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let ground = Lambertian::new(SolidColor::new(Vec3::new(0.5, 0.5, 0.5)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground,
+        )));
+
+        let diffuse = Lambertian::new(SolidColor::new(Vec3::new(0.8, 0.3, 0.2)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.5, 1.0, 0.0),
+            1.0,
+            diffuse,
+        )));
+
+        let gold = Conductor::gold(SolidColor::new(Vec3::new(0.3, 0.3, 0.3)));
+        objects.push(Box::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, gold)));
+
+        let plastic = Principled::new(SolidColor::new(Vec3::new(0.2, 0.4, 0.8)))
+            .with_roughness(SolidColor::new(Vec3::new(0.2, 0.2, 0.2)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(2.5, 1.0, 0.0),
+            1.0,
+            plastic,
+        )));
+
+        // A low sun from the left, a warm bulb in front and a spot light from above
+        let lights: Vec<Box<dyn Light>> = vec![
+            Box::new(DirectionalLight::new(
+                Vec3::new(1.0, -0.5, -0.3),
+                Vec3::new(1.0, 0.9, 0.75),
+            )),
+            Box::new(PointLight::new(
+                Vec3::new(-1.0, 2.5, 3.0),
+                Vec3::new(6.0, 4.5, 3.0),
+            )),
+            Box::new(SpotLight::new(
+                Vec3::new(2.5, 6.0, 1.0),
+                Vec3::new(2.5, 0.0, 0.0),
+                Vec3::new(20.0, 24.0, 30.0),
+                25.0,
+                15.0,
+            )),
+        ];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.02, 0.02, 0.04),
+        )
+    }
+
+    fn generate_environment(path: &str) -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let checker = CheckerTexture::new(
+            SolidColor::new(Vec3::new(0.2, 0.2, 0.2)),
+            SolidColor::new(Vec3::new(0.8, 0.8, 0.8)),
+        );
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(checker),
+        )));
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-2.5, 1.0, 0.0),
+            1.0,
+            Lambertian::new(SolidColor::new(Vec3::new(0.8, 0.8, 0.8))),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            Conductor::silver(SolidColor::new(Vec3::new(0.0, 0.0, 0.0))),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(2.5, 1.0, 0.0),
+            1.0,
+            Dielectric::new(1.5),
+        )));
+
+        // The environment lights the scene on its own, a plain sky stands in if it's missing
+        let (lights, background): (Vec<Box<dyn Light>>, Vec3) = match EnvironmentLight::load(path) {
+            Ok(environment) => (
+                vec![Box::new(
+                    environment.with_rotation(-30.0).with_intensity(1.2),
+                )],
+                Vec3::new(0.0, 0.0, 0.0),
+            ),
+            Err(error) => {
+                log::error!("Failed to load environment map '{}': {}", path, error);
+                (Vec::new(), Vec3::new(0.7, 0.8, 1.0))
+            }
+        };
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            background,
+        )
+    }
+
+    fn generate_sky(sun_elevation: f32, sun_azimuth: f32, turbidity: f32) -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let ground_albedo = Vec3::new(0.35, 0.33, 0.3);
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(SolidColor::new(ground_albedo)),
+        )));
+
+        // A small block of buildings around a glass sphere
+        let concrete = Lambertian::new(SolidColor::new(Vec3::new(0.7, 0.7, 0.68)));
+        let buildings = [
+            (Vec3::new(-6.0, 0.0, -4.0), Vec3::new(-3.0, 7.0, -1.0)),
+            (Vec3::new(-2.0, 0.0, -6.0), Vec3::new(1.0, 4.0, -3.0)),
+            (Vec3::new(2.0, 0.0, -5.0), Vec3::new(5.0, 10.0, -2.0)),
+            (Vec3::new(4.0, 0.0, 0.0), Vec3::new(6.0, 3.0, 3.0)),
+        ];
+        for (minimum, maximum) in buildings {
+            objects.push(Box::new(Cuboid::new(minimum, maximum, concrete.clone())));
+        }
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 1.5, 2.0),
+            1.5,
+            Dielectric::new(1.5),
+        )));
+
+        let sky = PhysicalSky::new(sun_elevation, sun_azimuth, turbidity)
+            .with_ground_albedo(ground_albedo);
+        let lights: Vec<Box<dyn Light>> =
+            vec![Box::new(sky.sky_light()), Box::new(sky.sun_light())];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_ies(path: &str) -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let white = Lambertian::new(SolidColor::new(Vec3::new(0.73, 0.73, 0.73)));
+        objects.push(Box::new(Rect::new(
+            Plane::ZX,
+            -10.0,
+            10.0,
+            -10.0,
+            10.0,
+            0.0,
+            white.clone(),
+        )));
+        objects.push(Box::new(Rect::new(
+            Plane::XY,
+            -10.0,
+            10.0,
+            0.0,
+            10.0,
+            -3.0,
+            white.clone(),
+        )));
+        objects.push(Box::new(Sphere::new(Vec3::new(0.0, 1.0, 0.0), 1.0, white)));
+
+        let profile = match IesProfile::load(path) {
+            Ok(profile) => Some(profile),
+            Err(error) => {
+                log::error!("Failed to load IES profile '{}': {}", path, error);
+                None
+            }
+        };
+
+        // Downlights close to the wall draw the scallops of their profile onto it
+        let mut lights: Vec<Box<dyn Light>> = Vec::new();
+        for x in [-4.5, 0.0, 4.5] {
+            let light = PointLight::new(Vec3::new(x, 7.0, -2.2), Vec3::new(15.0, 13.5, 12.0));
+            lights.push(Box::new(match &profile {
+                Some(profile) => light.with_profile(profile.clone()),
+                None => light,
+            }));
+        }
+
+        // A spot light with the same profile lights the sphere from the side
+        let spot = SpotLight::new(
+            Vec3::new(6.0, 4.0, 4.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(12.0, 14.0, 18.0),
+            40.0,
+            30.0,
+        );
+        lights.push(Box::new(match profile {
+            Some(profile) => spot.with_profile(profile),
+            None => spot,
+        }));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_many_lights() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = vec![
+            Box::new(Sphere::new(
+                Vec3::new(0.0, -1000.0, 0.0),
+                1000.0,
+                Lambertian::new(SolidColor::new(Vec3::new(0.5, 0.5, 0.5))),
+            )),
+            Box::new(Sphere::new(
+                Vec3::new(-3.0, 1.5, 0.0),
+                1.5,
+                Lambertian::new(SolidColor::new(Vec3::new(0.8, 0.8, 0.8))),
+            )),
+            Box::new(Sphere::new(
+                Vec3::new(0.0, 1.5, 0.0),
+                1.5,
+                Conductor::silver(SolidColor::new(Vec3::new(0.2, 0.2, 0.2))),
+            )),
+            Box::new(Sphere::new(
+                Vec3::new(3.0, 1.5, 0.0),
+                1.5,
+                Dielectric::new(1.5),
+            )),
+        ];
+
+        // A grid of small colored lamps facing down, most of them far from any given point
+        let mut rand = rand::thread_rng();
+        let mut lights: Vec<Box<dyn Light>> = Vec::new();
+        for a in -12..12 {
+            for b in -12..12 {
+                let x = a as f32 + 0.5;
+                let z = b as f32 + 0.5;
+                let y = rand.gen_range(4.0..7.0);
+                let color = Vec3::new(rand.gen(), rand.gen(), rand.gen()) * 40.0;
+
+                let lamp = FlipFace::new(Rect::new(
+                    Plane::ZX,
+                    x - 0.1,
+                    x + 0.1,
+                    z - 0.1,
+                    z + 0.1,
+                    y,
+                    DiffuseLight::new(SolidColor::new(color)),
+                ));
+                objects.push(Box::new(lamp.clone()));
+                lights.push(Box::new(AreaLight::new(Box::new(lamp))));
+            }
+        }
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_emissive_shapes() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            Lambertian::new(SolidColor::new(Vec3::new(0.5, 0.5, 0.5))),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(-1.5, 0.75, 3.0),
+            0.75,
+            Principled::new(SolidColor::new(Vec3::new(0.8, 0.8, 0.8)))
+                .with_roughness(SolidColor::new(Vec3::new(0.3, 0.3, 0.3))),
+        )));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(1.5, 0.75, 3.0),
+            0.75,
+            Conductor::gold(SolidColor::new(Vec3::new(0.2, 0.2, 0.2))),
+        )));
+
+        // A glowing globe, a checkered triangle and a lamp box, each sampled by its shape
+        let globe = Sphere::new(
+            Vec3::new(-4.0, 2.0, -1.0),
+            2.0,
+            DiffuseLight::new(ImageTexture::new("./assets/earthmap.jpg")).with_intensity(3.0),
+        );
+        objects.push(Box::new(globe.clone()));
+
+        let triangle = Triangle::new(
+            Vec3::new(-1.5, 0.5, -2.0),
+            Vec3::new(1.5, 0.5, -2.0),
+            Vec3::new(0.0, 4.0, -2.0),
+            DiffuseLight::new(CheckerTexture::new(
+                SolidColor::new(Vec3::new(0.5, 2.0, 4.0)),
+                SolidColor::new(Vec3::new(0.1, 0.1, 0.1)),
+            ))
+            .two_sided(),
+        );
+        objects.push(Box::new(triangle.clone()));
+
+        // Cuboids own their sides, the scene and the light each get one sharing the material
+        let glow = DiffuseLight::new(SolidColor::new(Vec3::new(4.0, 2.5, 1.0)));
+        let lamp = || {
+            Cuboid::new(
+                Vec3::new(3.0, 0.0, -2.0),
+                Vec3::new(5.0, 1.0, 0.0),
+                glow.clone(),
+            )
+        };
+        objects.push(Box::new(lamp()));
+
+        let lights: Vec<Box<dyn Light>> = vec![
+            Box::new(AreaLight::new(Box::new(globe))),
+            Box::new(AreaLight::new(Box::new(triangle))),
+            Box::new(AreaLight::new(Box::new(lamp()))),
+        ];
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    fn generate_clouds() -> World {
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        let ground = Lambertian::new(SolidColor::new(Vec3::new(0.48, 0.83, 0.53)));
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground.clone(),
+        )));
+
+        let cloud = Sphere::new(Vec3::new(-2.0, 4.0, -2.0), 2.5, ground.clone());
+        objects.push(Box::new(HeterogeneousMedium::new(
+            Box::new(cloud),
+            PerlinDensity::new(4.0, 1.5, 0.15),
+            HenyeyGreenstein::double_lobe(
+                SolidColor::new(Vec3::new(1.0, 1.0, 1.0)),
+                0.8,
+                -0.3,
+                0.9,
+            ),
+        )));
+
+        // Smoke rising from a point on the ground, widening and thinning out with height
+        let noise = PerlinDensity::new(1.0, 3.0, 0.0);
+        let plume_min = Vec3::new(0.5, 0.0, 0.5);
+        let plume_max = Vec3::new(3.5, 5.0, 3.5);
+        let plume = VoxelGrid::from_fn(plume_min, plume_max, [48, 80, 48], |point| {
+            let offset = Vec3::new(point.x - 2.0, 0.0, point.z - 2.0);
+            let radius = 0.2 + 0.25 * point.y;
+            let falloff = (1.0 - offset.magnitude() / radius).max(0.0);
+            6.0 * falloff * (-0.4 * point.y).exp() * noise.density(point)
+        });
+        objects.push(Box::new(HeterogeneousMedium::new(
+            Box::new(Cuboid::new(plume_min, plume_max, ground)),
+            plume,
+            HenyeyGreenstein::new(SolidColor::new(Vec3::new(0.4, 0.4, 0.4)), 0.4),
+        )));
+
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            Vec::new(),
+            Vec3::new(0.7, 0.8, 1.0),
+        )
+    }
+
+    fn generate_final_scene() -> World {
+        const BOXES_PER_SIDE: usize = 20;
+
+        let mut rand = rand::thread_rng();
+
+        let ground_material = Lambertian::new(SolidColor::new(Vec3::new(0.48, 0.83, 0.53)));
+        let mut ground_boxes: Vec<Box<dyn Hittable>> = Vec::new();
+        for i in 0..BOXES_PER_SIDE {
+            for j in 0..BOXES_PER_SIDE {
+                let w = 100.0;
+                let x0 = -1000.0 + i as f32 * w;
+                let z0 = -1000.0 + j as f32 * w;
+                let y0 = 0.0;
+                let x1 = x0 + w;
+                let y1 = rand.gen_range(1.0..101.0);
+                let z1 = z0 + w;
+
+                ground_boxes.push(Box::new(Cuboid::new(
+                    Vec3::new(x0, y0, z0),
+                    Vec3::new(x1, y1, z1),
+                    ground_material.clone(),
+                )));
+            }
+        }
+
+        let mut objects: Vec<Box<dyn Hittable>> = Vec::new();
+
+        objects.push(Box::new(BvhNode::new(ground_boxes, 0.0, 1.0)));
+
+        let diffuse_light = DiffuseLight::new(SolidColor::new(Vec3::new(7.0, 7.0, 7.0)));
+        let light = FlipFace::new(Rect::new(
+            Plane::ZX,
+            123.0,
+            423.0,
+            147.0,
+            412.0,
+            554.0,
+            diffuse_light,
+        ));
+        objects.push(Box::new(light.clone()));
+
+        let lights: Vec<Box<dyn Light>> = vec![Box::new(AreaLight::new(Box::new(light)))];
+
+        let center_1 = Vec3::new(400.0, 400.0, 200.0);
+        let center_2 = center_1 + Vec3::new(30.0, 0.0, 0.0);
+
+        let moving_sphere_material = Lambertian::new(SolidColor::new(Vec3::new(0.7, 0.3, 0.1)));
+        objects.push(Box::new(MovingSphere::new(
+            center_1,
+            center_2,
+            0.0,
+            1.0,
+            50.0,
+            moving_sphere_material,
+        )));
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(260.0, 150.0, 45.0),
+            50.0,
+            Dielectric::new(1.5),
+        )));
+
+        objects.push(Box::new(Sphere::new(
+            Vec3::new(0.0, 150.0, 145.0),
+            50.0,
+            Metal::new(Vec3::new(0.8, 0.8, 0.9), 1.0),
+        )));
+
+        let boundary = Sphere::new(Vec3::new(360.0, 150.0, 145.0), 70.0, Dielectric::new(1.5));
+        objects.push(Box::new(boundary.clone()));
+        objects.push(Box::new(ConstantMedium::new(
+            Box::new(boundary),
+            0.2,
+            Isotropic::new(SolidColor::new(Vec3::new(0.2, 0.4, 0.9))),
+        )));
 
         let boundary = Sphere::new(Vec3::new(0.0, 0.0, 0.0), 5000.0, Dielectric::new(1.5));
         objects.push(Box::new(ConstantMedium::new(
             Box::new(boundary),
             0.0001,
-            SolidColor::new(Vec3::new(1.0, 1.0, 1.0)),
+            Isotropic::new(SolidColor::new(Vec3::new(1.0, 1.0, 1.0))),
         )));
 
         let earth_map = Lambertian::new(ImageTexture::new("./assets/earthmap.jpg"));
@@ -931,6 +2401,10 @@ impl Application {
             Vec3::new(-100.0, 270.0, 395.0),
         )));
 
-        Box::new(BvhNode::new(objects, 0.0, 1.0))
+        World::new(
+            Box::new(BvhNode::new(objects, 0.0, 1.0)),
+            lights,
+            Vec3::new(0.0, 0.0, 0.0),
+        )
     }
 }
@@ -15,9 +15,40 @@ pub(crate) enum Scene {
     SimpleLight,
     Cornell,
     CornellSmoke,
+    CornellGlass,
+    CornellMedia,
+    Dispersion,
+    Metals,
+    FrostedGlass,
+    Principled,
+    Furnace,
+    RoughDiffuse,
+    Layered,
+    MixMaterial,
+    Iridescence,
+    Subsurface,
+    Bumps,
+    Cutout,
+    BrushedMetal,
+    Measured,
+    Emitters,
+    DeltaLights,
+    Environment,
+    Sky,
+    Ies,
+    ManyLights,
+    EmissiveShapes,
+    Clouds,
     Final,
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub(crate) enum Integrator {
+    PathTracer,
+    PhotonMapper,
+    Metropolis,
+}
+
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
 pub(crate) struct Arguments {
@@ -44,4 +75,61 @@ pub(crate) struct Arguments {
     /// Scene
     #[arg(long, value_enum, default_value_t = Scene::Random)]
     pub(crate) scene: Scene,
+
+    /// MERL measured BRDF shown by the measured scene, the binary files come from the MERL
+    /// BRDF database by Matusik et al.
+    #[arg(long, required_if_eq("scene", "measured"))]
+    pub(crate) brdf: Option<String>,
+
+    /// Equirectangular HDR image lighting the environment scene
+    #[arg(long, default_value = "./assets/sky.hdr")]
+    pub(crate) environment: String,
+
+    /// IES photometric profile of the lights in the IES scene
+    #[arg(long, default_value = "./assets/downlight.ies")]
+    pub(crate) ies: String,
+
+    /// Elevation of the sun above the horizon in degrees for the sky scene
+    #[arg(long, default_value_t = 30.0)]
+    pub(crate) sun_elevation: f32,
+
+    /// Azimuth of the sun in degrees for the sky scene
+    #[arg(long, default_value_t = 60.0)]
+    pub(crate) sun_azimuth: f32,
+
+    /// Haziness of the atmosphere in the sky scene, from 2 for clear to 10 for hazy
+    #[arg(long, default_value_t = 3.0)]
+    pub(crate) turbidity: f32,
+
+    /// Integrator
+    #[arg(long, value_enum, default_value_t = Integrator::PathTracer)]
+    pub(crate) integrator: Integrator,
+
+    /// Trace sampled wavelengths instead of RGB, needed for dispersion
+    #[arg(long)]
+    pub(crate) spectral: bool,
+
+    /// Photons per pass of the photon mapper
+    #[arg(long, default_value_t = 200_000)]
+    pub(crate) photons: u32,
+
+    /// Initial gather radius of the photon mapper in pixels
+    #[arg(long, default_value_t = 4.0)]
+    pub(crate) photon_radius: f32,
+
+    /// Bootstrap samples used to normalize Metropolis light transport
+    #[arg(long, default_value_t = 100_000)]
+    pub(crate) bootstrap_samples: u32,
+
+    /// Markov chains run by Metropolis light transport
+    #[arg(long, default_value_t = 1000)]
+    pub(crate) chains: u32,
+
+    /// Probability of a large step mutation replacing all primary samples
+    #[arg(long, default_value_t = 0.3)]
+    pub(crate) large_step_probability: f32,
+
+    /// Standard deviation of the small step mutation perturbing the primary samples
+    #[arg(long, default_value_t = 0.01)]
+    pub(crate) small_step_sigma: f32,
 }
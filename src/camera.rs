@@ -7,6 +7,7 @@
 use crate::{
     math::{self, Vec3},
     ray::Ray,
+    sampler,
 };
 
 use cgmath::InnerSpace;
@@ -82,11 +83,18 @@ impl Camera {
             self.origin - self.horizontal / 2.0 - self.vertical / 2.0 - self.focus_dist * self.w;
     }
 
+    /// Size of a pixel projected onto the plane the camera looks at.
+    pub(crate) fn pixel_footprint(&self, height: i32) -> f32 {
+        let distance = (self.look_from - self.look_at).magnitude();
+        let viewport_height = 2.0 * (self.fov.to_radians() / 2.0).tan();
+        distance * viewport_height / height as f32
+    }
+
     pub(crate) fn get_ray(&self, s: f32, t: f32) -> Ray {
         let rd = self.lens_radius * math::random_in_unit_disk();
         let offset = self.u * rd.x + self.v * rd.y;
 
-        let mut rand = rand::thread_rng();
+        let mut rand = sampler::rng();
         Ray::new(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
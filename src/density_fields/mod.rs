@@ -0,0 +1,17 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+pub(crate) mod perlin_density;
+pub(crate) mod voxel_grid;
+
+pub(crate) trait DensityField: Send + Sync {
+    fn density(&self, point: Vec3) -> f32;
+
+    /// Upper bound of the density anywhere in the field, used as majorant for tracking.
+    fn max_density(&self) -> f32;
+}
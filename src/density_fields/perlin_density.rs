@@ -0,0 +1,38 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{density_fields::DensityField, math::Vec3, perlin_noise::PerlinNoise};
+
+#[derive(Clone)]
+pub(crate) struct PerlinDensity {
+    noise: PerlinNoise,
+    density: f32,
+    scale: f32,
+    threshold: f32,
+}
+
+impl PerlinDensity {
+    pub(crate) fn new(density: f32, scale: f32, threshold: f32) -> Self {
+        Self {
+            noise: PerlinNoise::new(),
+            density,
+            scale,
+            threshold,
+        }
+    }
+}
+
+impl DensityField for PerlinDensity {
+    fn density(&self, point: Vec3) -> f32 {
+        let turbulence = self.noise.turbulence(self.scale * point, 7);
+        let coverage = ((turbulence - self.threshold) / (1.0 - self.threshold)).clamp(0.0, 1.0);
+        self.density * coverage
+    }
+
+    fn max_density(&self) -> f32 {
+        self.density
+    }
+}
@@ -0,0 +1,109 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{density_fields::DensityField, math::Vec3};
+
+#[derive(Clone)]
+pub(crate) struct VoxelGrid {
+    minimum: Vec3,
+    maximum: Vec3,
+    resolution: [usize; 3],
+    voxels: Vec<f32>,
+    max_density: f32,
+}
+
+impl VoxelGrid {
+    pub(crate) fn new(
+        minimum: Vec3,
+        maximum: Vec3,
+        resolution: [usize; 3],
+        voxels: Vec<f32>,
+    ) -> Self {
+        assert_eq!(voxels.len(), resolution[0] * resolution[1] * resolution[2]);
+
+        let max_density = voxels.iter().fold(0.0f32, |max, &voxel| max.max(voxel));
+
+        Self {
+            minimum,
+            maximum,
+            resolution,
+            voxels,
+            max_density,
+        }
+    }
+
+    /// Fills the grid by evaluating `density` at the center of every voxel.
+    pub(crate) fn from_fn(
+        minimum: Vec3,
+        maximum: Vec3,
+        resolution: [usize; 3],
+        density: impl Fn(Vec3) -> f32,
+    ) -> Self {
+        let size = maximum - minimum;
+        let mut voxels = Vec::with_capacity(resolution[0] * resolution[1] * resolution[2]);
+        for z in 0..resolution[2] {
+            for y in 0..resolution[1] {
+                for x in 0..resolution[0] {
+                    let point = Vec3::new(
+                        minimum.x + (x as f32 + 0.5) / resolution[0] as f32 * size.x,
+                        minimum.y + (y as f32 + 0.5) / resolution[1] as f32 * size.y,
+                        minimum.z + (z as f32 + 0.5) / resolution[2] as f32 * size.z,
+                    );
+                    voxels.push(density(point).max(0.0));
+                }
+            }
+        }
+
+        Self::new(minimum, maximum, resolution, voxels)
+    }
+
+    fn voxel(&self, x: usize, y: usize, z: usize) -> f32 {
+        self.voxels[(z * self.resolution[1] + y) * self.resolution[0] + x]
+    }
+}
+
+impl DensityField for VoxelGrid {
+    fn density(&self, point: Vec3) -> f32 {
+        let mut lower = [0; 3];
+        let mut weights = [0.0; 3];
+        for axis in 0..3 {
+            let extent = self.maximum[axis] - self.minimum[axis];
+            let position = (point[axis] - self.minimum[axis]) / extent;
+            if !(0.0..=1.0).contains(&position) {
+                return 0.0;
+            }
+
+            // Voxel values sit at the voxel centers
+            let continuous = (position * self.resolution[axis] as f32 - 0.5)
+                .clamp(0.0, (self.resolution[axis] - 1) as f32);
+            lower[axis] =
+                (continuous.floor() as usize).min(self.resolution[axis].saturating_sub(2));
+            weights[axis] = continuous - lower[axis] as f32;
+        }
+
+        let mut accumulator = 0.0;
+        for i in 0..(2 * 2 * 2) {
+            let x = i / (2 * 2);
+            let y = (i / 2) % 2;
+            let z = i % 2;
+
+            let index_x = (lower[0] + x).min(self.resolution[0] - 1);
+            let index_y = (lower[1] + y).min(self.resolution[1] - 1);
+            let index_z = (lower[2] + z).min(self.resolution[2] - 1);
+
+            accumulator += (x as f32 * weights[0] + (1 - x) as f32 * (1.0 - weights[0]))
+                * (y as f32 * weights[1] + (1 - y) as f32 * (1.0 - weights[1]))
+                * (z as f32 * weights[2] + (1 - z) as f32 * (1.0 - weights[2]))
+                * self.voxel(index_x, index_y, index_z);
+        }
+
+        accumulator
+    }
+
+    fn max_density(&self) -> f32 {
+        self.max_density
+    }
+}
@@ -0,0 +1,160 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+/// Piecewise constant distribution over `[0, 1)` proportional to the given values.
+#[derive(Clone, Debug)]
+pub(crate) struct Distribution1D {
+    values: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    pub(crate) fn new(values: Vec<f32>) -> Self {
+        let count = values.len() as f32;
+        let mut cdf = Vec::with_capacity(values.len() + 1);
+        cdf.push(0.0);
+        for value in &values {
+            cdf.push(cdf[cdf.len() - 1] + value.max(0.0) / count);
+        }
+
+        // Without any weight every segment is equally likely
+        let integral = cdf[cdf.len() - 1];
+        if integral > 0.0 {
+            cdf.iter_mut().for_each(|value| *value /= integral);
+        } else {
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, value)| *value = i as f32 / count);
+        }
+
+        Self {
+            values,
+            cdf,
+            integral,
+        }
+    }
+
+    pub(crate) fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    /// Returns the sampled position, its density and the index of its segment.
+    pub(crate) fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let index = self
+            .cdf
+            .partition_point(|&value| value <= u)
+            .saturating_sub(1)
+            .min(self.values.len() - 1);
+
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let offset = if width > 0.0 {
+            (u - self.cdf[index]) / width
+        } else {
+            0.0
+        };
+
+        let position = ((index as f32 + offset) / self.values.len() as f32).min(1.0 - f32::EPSILON);
+        (position, self.pdf(position), index)
+    }
+
+    pub(crate) fn pdf(&self, position: f32) -> f32 {
+        let index = ((position * self.values.len() as f32) as usize).min(self.values.len() - 1);
+        if self.integral > 0.0 {
+            self.values[index].max(0.0) / self.integral
+        } else {
+            1.0
+        }
+    }
+}
+
+/// Piecewise constant distribution over `[0, 1)²` given as rows of values, sampled by
+/// picking a row from the marginal distribution and then a column within it.
+#[derive(Clone, Debug)]
+pub(crate) struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub(crate) fn new(values: &[f32], width: usize, height: usize) -> Self {
+        let rows: Vec<Distribution1D> = values
+            .chunks(width)
+            .take(height)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect();
+        let marginal = Distribution1D::new(rows.iter().map(|row| row.integral()).collect());
+
+        Self { rows, marginal }
+    }
+
+    /// Returns the sampled position as column and row coordinate together with its density.
+    pub(crate) fn sample(&self, u: f32, v: f32) -> ((f32, f32), f32) {
+        let (y, marginal_pdf, row) = self.marginal.sample(v);
+        let (x, conditional_pdf, _) = self.rows[row].sample(u);
+        ((x, y), marginal_pdf * conditional_pdf)
+    }
+
+    pub(crate) fn pdf(&self, x: f32, y: f32) -> f32 {
+        let row = ((y * self.rows.len() as f32) as usize).min(self.rows.len() - 1);
+        self.marginal.pdf(y) * self.rows[row].pdf(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: usize = 8;
+    const HEIGHT: usize = 6;
+
+    /// Uneven values with empty cells and a fully empty row.
+    fn distribution() -> Distribution2D {
+        let values: Vec<f32> = (0..WIDTH * HEIGHT)
+            .map(|i| {
+                if i / WIDTH == 2 {
+                    0.0
+                } else {
+                    (i * 7 % 5) as f32
+                }
+            })
+            .collect();
+        Distribution2D::new(&values, WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn sample_matches_pdf() {
+        let distribution = distribution();
+        for i in 0..64 {
+            for j in 0..64 {
+                let (u, v) = ((i as f32 + 0.5) / 64.0, (j as f32 + 0.5) / 64.0);
+                let ((x, y), pdf) = distribution.sample(u, v);
+                assert!(pdf > 0.0, "sampled an empty cell at {x}, {y}");
+                assert!((pdf - distribution.pdf(x, y)).abs() <= pdf * 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn pdf_integrates_to_one() {
+        let distribution = distribution();
+        let resolution = 4 * WIDTH * HEIGHT;
+        let mut total = 0.0;
+        for i in 0..resolution {
+            for j in 0..resolution {
+                let x = (i as f32 + 0.5) / resolution as f32;
+                let y = (j as f32 + 0.5) / resolution as f32;
+                total += f64::from(distribution.pdf(x, y));
+            }
+        }
+
+        let integral = total / (resolution * resolution) as f64;
+        assert!(
+            (integral - 1.0).abs() < 1e-5,
+            "pdf integrates to {integral}"
+        );
+    }
+}
@@ -0,0 +1,41 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Image which several threads can splat radiance into at the same time.
+pub(crate) struct Film {
+    pixels: Vec<[AtomicU32; 3]>,
+}
+
+impl Film {
+    pub(crate) fn new(pixel_count: usize) -> Self {
+        Self {
+            pixels: (0..pixel_count).map(|_| Default::default()).collect(),
+        }
+    }
+
+    pub(crate) fn add(&self, index: usize, color: Vec3) {
+        for (channel, value) in self.pixels[index].iter().zip([color.x, color.y, color.z]) {
+            channel
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                    Some((f32::from_bits(bits) + value).to_bits())
+                })
+                .unwrap();
+        }
+    }
+
+    pub(crate) fn pixel(&self, index: usize) -> Vec3 {
+        let [r, g, b] = &self.pixels[index];
+        Vec3::new(
+            f32::from_bits(r.load(Ordering::Relaxed)),
+            f32::from_bits(g.load(Ordering::Relaxed)),
+            f32::from_bits(b.load(Ordering::Relaxed)),
+        )
+    }
+}
@@ -4,18 +4,24 @@
  * SPDX-License-Identifier: MIT
  */
 
-use crate::{materials::Material, math::Vec3, ray::Ray};
+use crate::{materials::Material, math::Vec3, media::MediumInterface, ray::Ray};
 
 use cgmath::InnerSpace;
 
+#[derive(Clone, Copy)]
 pub(crate) struct HitRecord<'a> {
     pub(crate) point: Vec3,
     pub(crate) normal: Vec3,
+    /// Derivative of the point along `u`, zero where the surface has no parameterization.
+    pub(crate) tangent: Vec3,
+    /// Derivative of the point along `v`.
+    pub(crate) bitangent: Vec3,
     pub(crate) t: f32,
     pub(crate) u: f32,
     pub(crate) v: f32,
     pub(crate) front_face: bool,
     pub(crate) material: &'a dyn Material,
+    pub(crate) medium_interface: Option<&'a MediumInterface>,
 }
 
 impl<'a> HitRecord<'a> {
@@ -27,4 +33,21 @@ impl<'a> HitRecord<'a> {
             -outward_normal
         };
     }
+
+    /// Copy of the record shaded with a perturbed normal, which is dropped if it would face
+    /// away from the incoming ray.
+    pub(crate) fn with_shading_normal(&self, ray: &Ray, outward_normal: Vec3) -> Self {
+        let normal = if self.front_face {
+            outward_normal
+        } else {
+            -outward_normal
+        };
+
+        let mut hit_record = *self;
+        if ray.direction().dot(normal) < 0.0 {
+            hit_record.normal = normal;
+        }
+
+        hit_record
+    }
 }
@@ -0,0 +1,158 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, math::Vec3, ray::Ray, sampler,
+    textures::Texture,
+};
+
+use rand::Rng;
+
+/// Cuts holes into a shape where the first channel of the opacity texture is low. Hits are
+/// either dropped below a fixed threshold or stochastically with the opacity.
+pub(crate) struct AlphaMask<T: Texture> {
+    shape: Box<dyn Hittable>,
+    opacity: T,
+    threshold: Option<f32>,
+    area: f32,
+}
+
+impl<T: Texture> AlphaMask<T> {
+    const AREA_SAMPLES: u32 = 256;
+    const SURFACE_ATTEMPTS: u32 = 64;
+
+    pub(crate) fn new(shape: Box<dyn Hittable>, opacity: T, threshold: f32) -> Self {
+        Self {
+            shape,
+            opacity,
+            threshold: Some(threshold),
+            area: 0.0,
+        }
+        .with_opaque_area()
+    }
+
+    /// Keeps every hit with the probability of its opacity, which renders semi transparent
+    /// surfaces without sorting them.
+    pub(crate) fn stochastic(shape: Box<dyn Hittable>, opacity: T) -> Self {
+        Self {
+            shape,
+            opacity,
+            threshold: None,
+            area: 0.0,
+        }
+        .with_opaque_area()
+    }
+
+    /// Estimates the area left over by the holes from the opacity at surface samples.
+    fn with_opaque_area(self) -> Self {
+        let mut opaque = 0.0;
+        for _ in 0..Self::AREA_SAMPLES {
+            let Some(hit_record) = self.shape.sample_surface(0.0) else {
+                continue;
+            };
+
+            let opacity = self.opacity(&hit_record);
+            opaque += match self.threshold {
+                Some(threshold) if opacity >= threshold => 1.0,
+                Some(_) => 0.0,
+                None => opacity.clamp(0.0, 1.0),
+            };
+        }
+
+        let area = self.shape.area() * opaque / Self::AREA_SAMPLES as f32;
+        Self { area, ..self }
+    }
+
+    fn opacity(&self, hit_record: &HitRecord) -> f32 {
+        self.opacity
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            .x
+    }
+
+    fn is_opaque(&self, hit_record: &HitRecord) -> bool {
+        let opacity = self.opacity(hit_record);
+        match self.threshold {
+            Some(threshold) => opacity >= threshold,
+            None => opacity >= 1.0 || sampler::rng().gen::<f32>() < opacity,
+        }
+    }
+
+    /// Whether `is_opaque` could keep the hit, which is what light sampling goes by.
+    fn may_be_opaque(&self, hit_record: &HitRecord) -> bool {
+        let opacity = self.opacity(hit_record);
+        match self.threshold {
+            Some(threshold) => opacity >= threshold,
+            None => opacity > 0.0,
+        }
+    }
+}
+
+impl<T: Texture> Hittable for AlphaMask<T> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        // Skip over the cut out hits until an opaque one is found
+        let mut time_min = time_min;
+        loop {
+            let hit_record = self.shape.hit(ray, time_min, time_max)?;
+            if self.is_opaque(&hit_record) {
+                return Some(hit_record);
+            }
+
+            time_min = hit_record.t + 0.0001;
+        }
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let mut time_min = time_min;
+        loop {
+            let hit_record = self.shape.hit_deterministic(ray, time_min, time_max)?;
+            if self.may_be_opaque(&hit_record) {
+                return Some(hit_record);
+            }
+
+            time_min = hit_record.t + 0.0001;
+        }
+    }
+
+    fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb> {
+        self.shape.bounding_box(time_start, time_end)
+    }
+
+    fn count(&self) -> u32 {
+        self.shape.count()
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        // Rejecting points in the holes leaves them uniform over the opaque part
+        (0..Self::SURFACE_ATTEMPTS).find_map(|_| {
+            self.shape
+                .sample_surface(time)
+                .filter(|hit_record| self.is_opaque(hit_record))
+        })
+    }
+
+    fn area(&self) -> f32 {
+        self.area
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        // Directions first crossing the shape in a hole are dropped, light seen through the
+        // hole is left to the other sampling strategies
+        let direction = self.shape.random_direction(origin, time)?;
+        let ray = Ray::new(origin, direction, time);
+        let hit_record = self.shape.hit_deterministic(&ray, 0.001, f32::INFINITY)?;
+        self.may_be_opaque(&hit_record).then_some(direction)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        let ray = Ray::new(origin, direction, time);
+        match self.shape.hit_deterministic(&ray, 0.001, f32::INFINITY) {
+            Some(hit_record) if self.may_be_opaque(&hit_record) => {
+                self.shape.pdf_value(origin, direction, time)
+            }
+            _ => 0.0,
+        }
+    }
+}
@@ -126,6 +126,74 @@ impl Hittable for BvhNode {
         }
     }
 
+    fn hit_surface(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, time_min, time_max) {
+            return None;
+        }
+
+        match &self.tree {
+            Node::Branch { left, right } => {
+                let left = left.hit_surface(ray, time_min, time_max);
+
+                let mut time_max = time_max;
+                if let Some(ref hit) = left {
+                    time_max = hit.t;
+                }
+
+                let right = right.hit_surface(ray, time_min, time_max);
+                if right.is_some() {
+                    right
+                } else {
+                    left
+                }
+            }
+            Node::Leaf { leaf } => leaf.hit_surface(ray, time_min, time_max),
+        }
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, time_min, time_max) {
+            return None;
+        }
+
+        match &self.tree {
+            Node::Branch { left, right } => {
+                let left = left.hit_deterministic(ray, time_min, time_max);
+
+                let mut time_max = time_max;
+                if let Some(ref hit) = left {
+                    time_max = hit.t;
+                }
+
+                let right = right.hit_deterministic(ray, time_min, time_max);
+                if right.is_some() {
+                    right
+                } else {
+                    left
+                }
+            }
+            Node::Leaf { leaf } => leaf.hit_deterministic(ray, time_min, time_max),
+        }
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        if !self.bounding_box.hit(ray, time_min, time_max) {
+            return 1.0;
+        }
+
+        match &self.tree {
+            Node::Branch { left, right } => {
+                let left = left.transmittance(ray, time_min, time_max);
+                if left <= 0.0 {
+                    return 0.0;
+                }
+
+                left * right.transmittance(ray, time_min, time_max)
+            }
+            Node::Leaf { leaf } => leaf.transmittance(ray, time_min, time_max),
+        }
+    }
+
     fn bounding_box(&self, _time_start: f32, _time_end: f32) -> Option<Aabb> {
         Some(self.bounding_box)
     }
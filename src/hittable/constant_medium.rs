@@ -10,68 +10,64 @@ use cgmath::InnerSpace;
 use rand::Rng;
 
 use crate::{
-    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, materials::isotropic::Isotropic,
-    math::Vec3, ray::Ray, textures::Texture,
+    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, materials::Material, math::Vec3,
+    ray::Ray, sampler,
 };
 
-pub(crate) struct ConstantMedium<T: Texture> {
+pub(crate) struct ConstantMedium<M: Material> {
     boundary: Box<dyn Hittable>,
     negative_inverse_density: f32,
-    phase_function: Isotropic<T>,
+    phase_function: M,
 }
 
-impl<T: Texture> ConstantMedium<T> {
-    pub(crate) fn new(boundary: Box<dyn Hittable>, density: f32, texture: T) -> Self {
+impl<M: Material> ConstantMedium<M> {
+    pub(crate) fn new(boundary: Box<dyn Hittable>, density: f32, phase_function: M) -> Self {
         Self {
             boundary,
             negative_inverse_density: -1.0 / density,
-            phase_function: Isotropic::new(texture),
+            phase_function,
         }
     }
-}
 
-impl<T: Texture> Hittable for ConstantMedium<T> {
-    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+    /// Part of the ray between `time_min` and `time_max` inside the boundary.
+    fn interval(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<(f32, f32)> {
         // TODO: Implement for shapes with holes
 
-        let mut record_1 = self.boundary.hit(ray, -f32::INFINITY, f32::INFINITY)?;
-        let mut record_2 = self.boundary.hit(ray, record_1.t + 0.0001, f32::INFINITY)?;
+        let record_1 = self.boundary.hit(ray, -f32::INFINITY, f32::INFINITY)?;
+        let record_2 = self.boundary.hit(ray, record_1.t + 0.0001, f32::INFINITY)?;
 
-        if record_1.t < time_min {
-            record_1.t = time_min;
-        }
-
-        if record_2.t > time_max {
-            record_2.t = time_max;
-        }
-
-        if record_1.t >= record_2.t {
-            return None;
-        }
+        let start = record_1.t.max(time_min).max(0.0);
+        let end = record_2.t.min(time_max);
+        (start < end).then_some((start, end))
+    }
+}
 
-        if record_1.t < 0.0 {
-            record_1.t = 0.0;
-        }
+impl<M: Material> Hittable for ConstantMedium<M> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let (start, end) = self.interval(ray, time_min, time_max)?;
 
-        let mut rand = rand::thread_rng();
+        let mut rand = sampler::rng();
         let ray_length = ray.direction().magnitude();
-        let distance_inside_boundary = (record_2.t - record_1.t) * ray_length;
+        let distance_inside_boundary = (end - start) * ray_length;
         let hit_distance = self.negative_inverse_density * rand.gen::<f32>().log(E);
 
         if hit_distance > distance_inside_boundary {
             return None;
         }
 
-        let t = record_1.t + hit_distance / ray_length;
+        let t = start + hit_distance / ray_length;
 
         Some(HitRecord {
             point: ray.at(t),
             normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent: Vec3::new(0.0, 0.0, 0.0),
+            bitangent: Vec3::new(0.0, 0.0, 0.0),
             t,
             u: 0.0,
             v: 0.0,
             front_face: false,
             material: &self.phase_function,
+            medium_interface: None,
         })
     }
 
@@ -82,4 +78,17 @@ impl<T: Texture> Hittable for ConstantMedium<T> {
     fn count(&self) -> u32 {
         self.boundary.count()
     }
+
+    fn hit_surface(&self, _ray: &Ray, _time_min: f32, _time_max: f32) -> Option<HitRecord> {
+        None
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        let Some((start, end)) = self.interval(ray, time_min, time_max) else {
+            return 1.0;
+        };
+
+        let distance_inside_boundary = (end - start) * ray.direction().magnitude();
+        (distance_inside_boundary / self.negative_inverse_density).exp()
+    }
 }
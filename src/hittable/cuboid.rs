@@ -96,9 +96,36 @@ impl<M: Clone + Material + 'static> Cuboid<M> {
     }
 }
 
+impl<M: Clone + Material> Cuboid<M> {
+    /// Every side faces the positive axis, flips the ones at the minimum corner outwards.
+    fn outward_normal(&self, hit_record: &HitRecord) -> Vec3 {
+        let axis = (0..3)
+            .max_by(|&a, &b| {
+                hit_record.normal[a]
+                    .abs()
+                    .total_cmp(&hit_record.normal[b].abs())
+            })
+            .unwrap();
+        let mut outward_normal = Vec3::new(0.0, 0.0, 0.0);
+        outward_normal[axis] = if (hit_record.point[axis] - self.box_min[axis]).abs()
+            < (hit_record.point[axis] - self.box_max[axis]).abs()
+        {
+            -1.0
+        } else {
+            1.0
+        };
+
+        outward_normal
+    }
+}
+
 impl<M: Clone + Material> Hittable for Cuboid<M> {
     fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
-        self.sides.hit(ray, time_min, time_max)
+        let mut hit_record = self.sides.hit(ray, time_min, time_max)?;
+        let outward_normal = self.outward_normal(&hit_record);
+        hit_record.set_face_normal(ray, outward_normal);
+
+        Some(hit_record)
     }
 
     fn bounding_box(&self, _time_start: f32, _time_end: f32) -> Option<Aabb> {
@@ -108,4 +135,22 @@ impl<M: Clone + Material> Hittable for Cuboid<M> {
     fn count(&self) -> u32 {
         self.sides.count()
     }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        let mut hit_record = self.sides.sample_surface(time)?;
+        hit_record.normal = self.outward_normal(&hit_record);
+        Some(hit_record)
+    }
+
+    fn area(&self) -> f32 {
+        self.sides.area()
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        self.sides.random_direction(origin, time)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        self.sides.pdf_value(origin, direction, time)
+    }
 }
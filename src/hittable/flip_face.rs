@@ -0,0 +1,59 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{aabb::Aabb, hit_record::HitRecord, hittable::Hittable, math::Vec3, ray::Ray};
+
+/// Swaps the front and back face of a shape, mostly used to turn one sided lights around.
+#[derive(Clone)]
+pub(crate) struct FlipFace<H: Hittable> {
+    shape: H,
+}
+
+impl<H: Hittable> FlipFace<H> {
+    pub(crate) fn new(shape: H) -> Self {
+        Self { shape }
+    }
+}
+
+impl<H: Hittable> Hittable for FlipFace<H> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let mut hit_record = self.shape.hit(ray, time_min, time_max)?;
+        hit_record.front_face = !hit_record.front_face;
+        Some(hit_record)
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let mut hit_record = self.shape.hit_deterministic(ray, time_min, time_max)?;
+        hit_record.front_face = !hit_record.front_face;
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb> {
+        self.shape.bounding_box(time_start, time_end)
+    }
+
+    fn count(&self) -> u32 {
+        self.shape.count()
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        let mut hit_record = self.shape.sample_surface(time)?;
+        hit_record.normal = -hit_record.normal;
+        Some(hit_record)
+    }
+
+    fn area(&self) -> f32 {
+        self.shape.area()
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        self.shape.random_direction(origin, time)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        self.shape.pdf_value(origin, direction, time)
+    }
+}
@@ -0,0 +1,137 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use cgmath::InnerSpace;
+use rand::Rng;
+
+use crate::{
+    aabb::Aabb, density_fields::DensityField, hit_record::HitRecord, hittable::Hittable,
+    materials::Material, math::Vec3, ray::Ray, sampler,
+};
+
+/// Participating medium whose density varies inside the boundary. Scattering events are
+/// sampled with delta tracking against the majorant of the density field.
+pub(crate) struct HeterogeneousMedium<D: DensityField, M: Material> {
+    boundary: Box<dyn Hittable>,
+    density_field: D,
+    phase_function: M,
+}
+
+impl<D: DensityField, M: Material> HeterogeneousMedium<D, M> {
+    pub(crate) fn new(boundary: Box<dyn Hittable>, density_field: D, phase_function: M) -> Self {
+        Self {
+            boundary,
+            density_field,
+            phase_function,
+        }
+    }
+
+    /// Collects the parts of the ray inside the boundary between `time_min` and `time_max`.
+    /// Crossings are paired up from the start of the ray, so boundaries with holes or
+    /// concavities split into several segments.
+    fn segments(&self, ray: &Ray, time_min: f32, time_max: f32) -> Vec<(f32, f32)> {
+        let time_min = time_min.max(0.0);
+
+        let mut segments = Vec::new();
+        let mut time = -f32::INFINITY;
+        while let Some(record_1) = self.boundary.hit(ray, time, time_max) {
+            let Some(record_2) = self.boundary.hit(ray, record_1.t + 0.0001, f32::INFINITY) else {
+                break;
+            };
+
+            let start = record_1.t.max(time_min);
+            let end = record_2.t.min(time_max);
+            if start < end {
+                segments.push((start, end));
+            }
+
+            time = record_2.t + 0.0001;
+        }
+
+        segments
+    }
+
+    /// Estimates the fraction of light passing between `time_min` and `time_max` with ratio
+    /// tracking, which stays unbiased without ever terminating the estimate early.
+    fn ratio_tracking(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        let max_density = self.density_field.max_density();
+        if max_density <= 0.0 {
+            return 1.0;
+        }
+
+        let mut rand = sampler::rng();
+        let ray_length = ray.direction().magnitude();
+        let mut transmittance = 1.0;
+        for (start, end) in self.segments(ray, time_min, time_max) {
+            let mut t = start;
+            loop {
+                t -= (1.0 - rand.gen::<f32>()).ln() / (max_density * ray_length);
+                if t >= end {
+                    break;
+                }
+
+                transmittance *= 1.0 - self.density_field.density(ray.at(t)) / max_density;
+            }
+        }
+
+        transmittance
+    }
+}
+
+impl<D: DensityField, M: Material> Hittable for HeterogeneousMedium<D, M> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let max_density = self.density_field.max_density();
+        if max_density <= 0.0 {
+            return None;
+        }
+
+        let mut rand = sampler::rng();
+        let ray_length = ray.direction().magnitude();
+        for (start, end) in self.segments(ray, time_min, time_max) {
+            let mut t = start;
+            loop {
+                t -= (1.0 - rand.gen::<f32>()).ln() / (max_density * ray_length);
+                if t >= end {
+                    break;
+                }
+
+                // Collisions with the fictitious part of the majorant are null and ignored
+                if rand.gen::<f32>() * max_density < self.density_field.density(ray.at(t)) {
+                    return Some(HitRecord {
+                        point: ray.at(t),
+                        normal: Vec3::new(0.0, 0.0, 0.0),
+                        tangent: Vec3::new(0.0, 0.0, 0.0),
+                        bitangent: Vec3::new(0.0, 0.0, 0.0),
+                        t,
+                        u: 0.0,
+                        v: 0.0,
+                        front_face: false,
+                        material: &self.phase_function,
+                        medium_interface: None,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb> {
+        self.boundary.bounding_box(time_start, time_end)
+    }
+
+    fn count(&self) -> u32 {
+        self.boundary.count()
+    }
+
+    fn hit_surface(&self, _ray: &Ray, _time_min: f32, _time_max: f32) -> Option<HitRecord> {
+        None
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        self.ratio_tracking(ray, time_min, time_max)
+    }
+}
@@ -4,7 +4,9 @@
  * SPDX-License-Identifier: MIT
  */
 
-use crate::{aabb::Aabb, hit_record::HitRecord, hittable::Hittable, ray::Ray};
+use crate::{aabb::Aabb, hit_record::HitRecord, hittable::Hittable, math::Vec3, ray::Ray, sampler};
+
+use rand::Rng;
 
 pub(crate) struct List {
     objects: Vec<Box<dyn Hittable>>,
@@ -14,6 +16,21 @@ impl List {
     pub(crate) fn new(objects: Vec<Box<dyn Hittable>>) -> Self {
         Self { objects }
     }
+
+    /// Picks an object with a probability proportional to its area.
+    fn pick_by_area(&self) -> Option<&dyn Hittable> {
+        let mut target = sampler::rng().gen::<f32>() * self.area();
+        let mut picked = None;
+        for object in self.objects.iter().filter(|object| object.area() > 0.0) {
+            picked = Some(&**object);
+            target -= object.area();
+            if target < 0.0 {
+                break;
+            }
+        }
+
+        picked
+    }
 }
 
 impl Hittable for List {
@@ -30,6 +47,39 @@ impl Hittable for List {
         hit_anything
     }
 
+    fn hit_surface(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let mut closest = time_max;
+        let mut hit_anything = None;
+        for object in &self.objects {
+            if let Some(hit) = object.hit_surface(ray, time_min, closest) {
+                closest = hit.t;
+                hit_anything = Some(hit);
+            }
+        }
+
+        hit_anything
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let mut closest = time_max;
+        let mut hit_anything = None;
+        for object in &self.objects {
+            if let Some(hit) = object.hit_deterministic(ray, time_min, closest) {
+                closest = hit.t;
+                hit_anything = Some(hit);
+            }
+        }
+
+        hit_anything
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        self.objects
+            .iter()
+            .map(|object| object.transmittance(ray, time_min, time_max))
+            .product()
+    }
+
     fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb> {
         let object = self.objects.first()?;
         let first_aabb = object.bounding_box(time_start, time_end)?;
@@ -46,4 +96,30 @@ impl Hittable for List {
     fn count(&self) -> u32 {
         self.objects.iter().map(|object| object.count()).sum()
     }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        self.pick_by_area()?.sample_surface(time)
+    }
+
+    fn area(&self) -> f32 {
+        self.objects.iter().map(|object| object.area()).sum()
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        self.pick_by_area()?.random_direction(origin, time)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        let area = self.area();
+        if area <= 0.0 {
+            return 0.0;
+        }
+
+        // Every object picked by its area could have produced the direction
+        self.objects
+            .iter()
+            .filter(|object| object.area() > 0.0)
+            .map(|object| object.area() / area * object.pdf_value(origin, direction, time))
+            .sum()
+    }
 }
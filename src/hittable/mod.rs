@@ -4,17 +4,24 @@
  * SPDX-License-Identifier: MIT
  */
 
-use crate::{aabb::Aabb, hit_record::HitRecord, ray::Ray};
+use crate::{aabb::Aabb, hit_record::HitRecord, math::Vec3, ray::Ray};
 
+use cgmath::InnerSpace;
+
+pub(crate) mod alpha_mask;
 pub(crate) mod bvh_node;
 pub(crate) mod constant_medium;
 pub(crate) mod cuboid;
+pub(crate) mod flip_face;
+pub(crate) mod heterogeneous_medium;
 pub(crate) mod list;
+pub(crate) mod medium_boundary;
 pub(crate) mod moving_sphere;
 pub(crate) mod rect;
 pub(crate) mod rotation;
 pub(crate) mod sphere;
 pub(crate) mod translation;
+pub(crate) mod triangle;
 
 pub(crate) trait Hittable: Send + Sync {
     fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord>;
@@ -22,4 +29,60 @@ pub(crate) trait Hittable: Send + Sync {
     fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb>;
 
     fn count(&self) -> u32;
+
+    /// Closest hit with a surface, passing through volumes instead of sampling collisions
+    /// inside of them. Shadow rays combine it with `transmittance`.
+    fn hit_surface(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        self.hit(ray, time_min, time_max)
+    }
+
+    /// Fraction of light passing through the volumes of the shape between `time_min` and
+    /// `time_max`, surfaces are left to `hit_surface`.
+    fn transmittance(&self, _ray: &Ray, _time_min: f32, _time_max: f32) -> f32 {
+        1.0
+    }
+
+    /// Closest hit as seen by light sampling. Shapes resolving hits stochastically report
+    /// every hit they could return instead, so light densities agree between calls.
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        self.hit(ray, time_min, time_max)
+    }
+
+    /// Picks a point uniformly distributed over the surface, used to emit light from it.
+    fn sample_surface(&self, _time: f32) -> Option<HitRecord> {
+        None
+    }
+
+    fn area(&self) -> f32 {
+        0.0
+    }
+
+    /// Picks a direction from `origin` towards the surface, the point it reaches is the
+    /// closest intersection along it. Defaults to points uniform over the surface.
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        let record = self.sample_surface(time)?;
+        Some(record.point - origin)
+    }
+
+    /// Density over solid angle of `random_direction` picking `direction` from `origin`.
+    /// The default matches uniform surface points of shapes crossed at most once by a ray.
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        let area = self.area();
+        if area <= 0.0 {
+            return 0.0;
+        }
+
+        let ray = Ray::new(origin, direction, time);
+        let Some(record) = self.hit_deterministic(&ray, 0.001, f32::INFINITY) else {
+            return 0.0;
+        };
+
+        let distance_squared = record.t * record.t * direction.magnitude2();
+        let cosine = record.normal.dot(direction.normalize()).abs();
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+
+        distance_squared / (cosine * area)
+    }
 }
@@ -47,6 +47,22 @@ impl<M: Material> MovingSphere<M> {
 
         (phi / (2.0 * PI), theta / PI)
     }
+
+    fn calculate_tangents(point: Vec3, radius: f32) -> (Vec3, Vec3) {
+        let sin_theta = (point.x * point.x + point.z * point.z)
+            .sqrt()
+            .max(f32::EPSILON);
+        let tangent = 2.0 * PI * radius * Vec3::new(point.z, 0.0, -point.x);
+        let bitangent = PI
+            * radius
+            * Vec3::new(
+                -point.x * point.y / sin_theta,
+                sin_theta,
+                -point.y * point.z / sin_theta,
+            );
+
+        (tangent, bitangent)
+    }
 }
 
 impl<M: Material> MovingSphere<M> {
@@ -80,14 +96,18 @@ impl<M: Material> Hittable for MovingSphere<M> {
 
         let outward_normal = (ray.at(root) - self.center(ray.time())) / self.radius;
         let (u, v) = Self::calculate_uv(outward_normal);
+        let (tangent, bitangent) = Self::calculate_tangents(outward_normal, self.radius);
         let mut hit_record = HitRecord {
             point: ray.at(root),
             normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent,
+            bitangent,
             t: root,
             u,
             v,
             front_face: false,
             material: &self.material,
+            medium_interface: None,
         };
 
         hit_record.set_face_normal(ray, outward_normal);
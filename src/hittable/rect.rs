@@ -6,9 +6,11 @@
 
 use crate::{
     aabb::Aabb, hit_record::HitRecord, hittable::Hittable, materials::Material, math::Vec3,
-    ray::Ray,
+    ray::Ray, sampler,
 };
 
+use rand::Rng;
+
 #[derive(Clone, Copy, Debug)]
 pub enum Plane {
     XY,
@@ -47,15 +49,30 @@ impl<M: Material> Rect<M> {
             material,
         }
     }
-}
 
-impl<M: Material> Hittable for Rect<M> {
-    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
-        let (k_axis, a_axis, b_axis) = match &self.plane {
+    fn get_axises(&self) -> (usize, usize, usize) {
+        match self.plane {
             Plane::XY => (2, 0, 1),
             Plane::YZ => (0, 1, 2),
             Plane::ZX => (1, 2, 0),
-        };
+        }
+    }
+
+    fn calculate_tangents(&self) -> (Vec3, Vec3) {
+        let (_, a_axis, b_axis) = self.get_axises();
+
+        let mut tangent = Vec3::new(0.0, 0.0, 0.0);
+        tangent[a_axis] = self.a1 - self.a0;
+        let mut bitangent = Vec3::new(0.0, 0.0, 0.0);
+        bitangent[b_axis] = self.b1 - self.b0;
+
+        (tangent, bitangent)
+    }
+}
+
+impl<M: Material> Hittable for Rect<M> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let (k_axis, a_axis, b_axis) = self.get_axises();
 
         let t = (self.k - ray.origin()[k_axis]) / ray.direction()[k_axis];
         if t < time_min || t > time_max {
@@ -68,14 +85,18 @@ impl<M: Material> Hittable for Rect<M> {
             return None;
         }
 
+        let (tangent, bitangent) = self.calculate_tangents();
         let mut hit_record = HitRecord {
             point: ray.at(t),
             normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent,
+            bitangent,
             t,
             u: (a - self.a0) / (self.a1 - self.a0),
             v: (b - self.b0) / (self.b1 - self.b0),
             front_face: false,
             material: &self.material,
+            medium_interface: None,
         };
 
         let mut outward_normal = Vec3::new(0.0, 0.0, 0.0);
@@ -105,4 +126,38 @@ impl<M: Material> Hittable for Rect<M> {
     fn count(&self) -> u32 {
         1
     }
+
+    fn sample_surface(&self, _time: f32) -> Option<HitRecord> {
+        let (k_axis, a_axis, b_axis) = self.get_axises();
+
+        let mut rand = sampler::rng();
+        let u = rand.gen::<f32>();
+        let v = rand.gen::<f32>();
+
+        let mut point = Vec3::new(0.0, 0.0, 0.0);
+        point[k_axis] = self.k;
+        point[a_axis] = self.a0 + u * (self.a1 - self.a0);
+        point[b_axis] = self.b0 + v * (self.b1 - self.b0);
+
+        let mut normal = Vec3::new(0.0, 0.0, 0.0);
+        normal[k_axis] = 1.0;
+
+        let (tangent, bitangent) = self.calculate_tangents();
+        Some(HitRecord {
+            point,
+            normal,
+            tangent,
+            bitangent,
+            t: 0.0,
+            u,
+            v,
+            front_face: true,
+            material: &self.material,
+            medium_interface: None,
+        })
+    }
+
+    fn area(&self) -> f32 {
+        (self.a1 - self.a0) * (self.b1 - self.b0)
+    }
 }
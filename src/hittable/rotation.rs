@@ -98,39 +98,86 @@ impl Rotation {
     }
 }
 
+impl Rotation {
+    /// Rotates a world space vector into the space of the wrapped shape.
+    fn to_object(&self, vector: Vec3) -> Vec3 {
+        let (_, a_axis, b_axis) = self.axis.get_axises();
+        let mut rotated = vector;
+        rotated[a_axis] = self.cos_theta * vector[a_axis] + self.sin_theta * vector[b_axis];
+        rotated[b_axis] = -self.sin_theta * vector[a_axis] + self.cos_theta * vector[b_axis];
+        rotated
+    }
+
+    /// Rotates a vector of the wrapped shape back into world space.
+    fn to_world(&self, vector: Vec3) -> Vec3 {
+        let (_, a_axis, b_axis) = self.axis.get_axises();
+        let mut rotated = vector;
+        rotated[a_axis] = self.cos_theta * vector[a_axis] - self.sin_theta * vector[b_axis];
+        rotated[b_axis] = self.sin_theta * vector[a_axis] + self.cos_theta * vector[b_axis];
+        rotated
+    }
+
+    fn rotated_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            self.to_object(ray.origin()),
+            self.to_object(ray.direction()),
+            ray.time(),
+        )
+        .with_wavelength(ray.wavelength())
+    }
+
+    fn hit_to_world<'a>(&self, mut hit: HitRecord<'a>) -> HitRecord<'a> {
+        hit.point = self.to_world(hit.point);
+        hit.normal = self.to_world(hit.normal);
+        hit.tangent = self.to_world(hit.tangent);
+        hit.bitangent = self.to_world(hit.bitangent);
+        hit
+    }
+}
+
 impl Hittable for Rotation {
     fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
-        let (_, a_axis, b_axis) = self.axis.get_axises();
-        let mut origin = ray.origin();
-        let mut direction = ray.direction();
-
-        origin[a_axis] =
-            self.cos_theta * ray.origin()[a_axis] + self.sin_theta * ray.origin()[b_axis];
-        origin[b_axis] =
-            -self.sin_theta * ray.origin()[a_axis] + self.cos_theta * ray.origin()[b_axis];
-        direction[a_axis] =
-            self.cos_theta * ray.direction()[a_axis] + self.sin_theta * ray.direction()[b_axis];
-        direction[b_axis] =
-            -self.sin_theta * ray.direction()[a_axis] + self.cos_theta * ray.direction()[b_axis];
-
-        let rotated_ray = Ray::new(origin, direction, ray.time());
         self.hittable
-            .hit(&rotated_ray, time_min, time_max)
-            .map(|mut hit| {
-                let mut point = hit.point;
-                let mut normal = hit.normal;
-                point[a_axis] =
-                    self.cos_theta * hit.point[a_axis] - self.sin_theta * hit.point[b_axis];
-                point[b_axis] =
-                    self.sin_theta * hit.point[a_axis] + self.cos_theta * hit.point[b_axis];
-                normal[a_axis] =
-                    self.cos_theta * hit.normal[a_axis] - self.sin_theta * hit.normal[b_axis];
-                normal[b_axis] =
-                    self.sin_theta * hit.normal[a_axis] + self.cos_theta * hit.normal[b_axis];
-                hit.point = point;
-                hit.normal = normal;
-                hit
-            })
+            .hit(&self.rotated_ray(ray), time_min, time_max)
+            .map(|hit| self.hit_to_world(hit))
+    }
+
+    fn hit_surface(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        self.hittable
+            .hit_surface(&self.rotated_ray(ray), time_min, time_max)
+            .map(|hit| self.hit_to_world(hit))
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        self.hittable
+            .transmittance(&self.rotated_ray(ray), time_min, time_max)
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        self.hittable
+            .hit_deterministic(&self.rotated_ray(ray), time_min, time_max)
+            .map(|hit| self.hit_to_world(hit))
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        self.hittable
+            .sample_surface(time)
+            .map(|hit| self.hit_to_world(hit))
+    }
+
+    fn area(&self) -> f32 {
+        self.hittable.area()
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        self.hittable
+            .random_direction(self.to_object(origin), time)
+            .map(|direction| self.to_world(direction))
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        self.hittable
+            .pdf_value(self.to_object(origin), self.to_object(direction), time)
     }
 
     fn bounding_box(&self, _time_start: f32, _time_end: f32) -> Option<Aabb> {
@@ -5,11 +5,18 @@
  */
 
 use crate::{
-    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, materials::Material, math::Vec3,
+    aabb::Aabb,
+    hit_record::HitRecord,
+    hittable::Hittable,
+    materials::Material,
+    math::{self, Vec3},
+    onb::Onb,
     ray::Ray,
+    sampler,
 };
 
 use cgmath::InnerSpace;
+use rand::Rng;
 use std::f32::consts::PI;
 
 #[derive(Clone)]
@@ -34,6 +41,40 @@ impl<M: Material> Sphere<M> {
 
         (phi / (2.0 * PI), theta / PI)
     }
+
+    fn calculate_tangents(point: Vec3, radius: f32) -> (Vec3, Vec3) {
+        let sin_theta = (point.x * point.x + point.z * point.z)
+            .sqrt()
+            .max(f32::EPSILON);
+        let tangent = 2.0 * PI * radius * Vec3::new(point.z, 0.0, -point.x);
+        let bitangent = PI
+            * radius
+            * Vec3::new(
+                -point.x * point.y / sin_theta,
+                sin_theta,
+                -point.y * point.z / sin_theta,
+            );
+
+        (tangent, bitangent)
+    }
+
+    /// One minus the cosine of the half angle of the cone the sphere covers seen from
+    /// outside of it.
+    fn cone_size(&self, origin: Vec3) -> Option<f32> {
+        let distance_squared = (self.center - origin).magnitude2();
+        let radius_squared = self.radius * self.radius;
+        if distance_squared <= radius_squared {
+            return None;
+        }
+
+        // Distant spheres round the cosine to one, the series expansion keeps their size
+        let sin_squared = radius_squared / distance_squared;
+        if sin_squared < 0.00068523 {
+            return Some(sin_squared / 2.0);
+        }
+
+        Some(1.0 - (1.0 - sin_squared).sqrt())
+    }
 }
 
 impl<M: Material> Hittable for Sphere<M> {
@@ -59,14 +100,18 @@ impl<M: Material> Hittable for Sphere<M> {
 
         let outward_normal = (ray.at(root) - self.center) / self.radius;
         let (u, v) = Self::calculate_uv(outward_normal);
+        let (tangent, bitangent) = Self::calculate_tangents(outward_normal, self.radius);
         let mut hit_record = HitRecord {
             point: ray.at(root),
             normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent,
+            bitangent,
             t: root,
             u,
             v,
             front_face: false,
             material: &self.material,
+            medium_interface: None,
         };
 
         hit_record.set_face_normal(ray, outward_normal);
@@ -85,4 +130,63 @@ impl<M: Material> Hittable for Sphere<M> {
     fn count(&self) -> u32 {
         1
     }
+
+    fn sample_surface(&self, _time: f32) -> Option<HitRecord> {
+        let outward_normal = math::random_unit_vector();
+        let (u, v) = Self::calculate_uv(outward_normal);
+        let (tangent, bitangent) = Self::calculate_tangents(outward_normal, self.radius);
+        Some(HitRecord {
+            point: self.center + self.radius * outward_normal,
+            normal: outward_normal,
+            tangent,
+            bitangent,
+            t: 0.0,
+            u,
+            v,
+            front_face: true,
+            material: &self.material,
+            medium_interface: None,
+        })
+    }
+
+    fn area(&self) -> f32 {
+        4.0 * PI * self.radius * self.radius
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        // Points inside see the whole sphere, uniform points on it serve them well enough
+        let Some(cone_size) = self.cone_size(origin) else {
+            let record = self.sample_surface(time)?;
+            return Some(record.point - origin);
+        };
+
+        // Uniform directions inside the cone of the visible cap
+        let mut rand = sampler::rng();
+        let one_minus_cos = rand.gen::<f32>() * cone_size;
+        let cos_theta = 1.0 - one_minus_cos;
+        let sin_theta = (one_minus_cos * (2.0 - one_minus_cos)).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand.gen::<f32>();
+        let direction = Onb::from_w(self.center - origin).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ));
+
+        Some(direction)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        let ray = Ray::new(origin, direction, time);
+        let Some(record) = self.hit(&ray, 0.001, f32::INFINITY) else {
+            return 0.0;
+        };
+
+        let Some(cone_size) = self.cone_size(origin) else {
+            let distance_squared = record.t * record.t * direction.magnitude2();
+            let cosine = record.normal.dot(direction.normalize()).abs();
+            return distance_squared / (cosine * self.area()).max(f32::EPSILON);
+        };
+
+        1.0 / (2.0 * PI * cone_size)
+    }
 }
@@ -20,14 +20,20 @@ impl Translation {
     }
 }
 
-impl Hittable for Translation {
-    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
-        let moved_ray = Ray::new(
+impl Translation {
+    fn moved_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
             ray.origin() - self.displacement,
             ray.direction(),
             ray.time(),
-        );
+        )
+        .with_wavelength(ray.wavelength())
+    }
+}
 
+impl Hittable for Translation {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let moved_ray = self.moved_ray(ray);
         let mut hit_record = self.hittable.hit(&moved_ray, time_min, time_max)?;
 
         hit_record.point += self.displacement;
@@ -36,6 +42,53 @@ impl Hittable for Translation {
         Some(hit_record)
     }
 
+    fn hit_surface(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let moved_ray = self.moved_ray(ray);
+        let mut hit_record = self.hittable.hit_surface(&moved_ray, time_min, time_max)?;
+
+        hit_record.point += self.displacement;
+        hit_record.set_face_normal(&moved_ray, hit_record.normal);
+
+        Some(hit_record)
+    }
+
+    fn transmittance(&self, ray: &Ray, time_min: f32, time_max: f32) -> f32 {
+        self.hittable
+            .transmittance(&self.moved_ray(ray), time_min, time_max)
+    }
+
+    fn hit_deterministic(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        let moved_ray = self.moved_ray(ray);
+        let mut hit_record = self
+            .hittable
+            .hit_deterministic(&moved_ray, time_min, time_max)?;
+
+        hit_record.point += self.displacement;
+        hit_record.set_face_normal(&moved_ray, hit_record.normal);
+
+        Some(hit_record)
+    }
+
+    fn sample_surface(&self, time: f32) -> Option<HitRecord> {
+        let mut hit_record = self.hittable.sample_surface(time)?;
+        hit_record.point += self.displacement;
+        Some(hit_record)
+    }
+
+    fn area(&self) -> f32 {
+        self.hittable.area()
+    }
+
+    fn random_direction(&self, origin: Vec3, time: f32) -> Option<Vec3> {
+        self.hittable
+            .random_direction(origin - self.displacement, time)
+    }
+
+    fn pdf_value(&self, origin: Vec3, direction: Vec3, time: f32) -> f32 {
+        self.hittable
+            .pdf_value(origin - self.displacement, direction, time)
+    }
+
     fn bounding_box(&self, time_start: f32, time_end: f32) -> Option<Aabb> {
         let mut output_box = self.hittable.bounding_box(time_start, time_end)?;
 
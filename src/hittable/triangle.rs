@@ -0,0 +1,165 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb, hit_record::HitRecord, hittable::Hittable, materials::Material, math::Vec3,
+    onb::Onb, ray::Ray, sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+
+#[derive(Clone)]
+pub(crate) struct Triangle<M: Material> {
+    vertices: [Vec3; 3],
+    uvs: [(f32, f32); 3],
+    material: M,
+}
+
+impl<M: Material> Triangle<M> {
+    pub(crate) fn new(a: Vec3, b: Vec3, c: Vec3, material: M) -> Self {
+        Self {
+            vertices: [a, b, c],
+            uvs: [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0)],
+            material,
+        }
+    }
+
+    pub(crate) fn with_uvs(self, uvs: [(f32, f32); 3]) -> Self {
+        Self { uvs, ..self }
+    }
+
+    fn calculate_tangents(&self, normal: Vec3) -> (Vec3, Vec3) {
+        let [a, b, c] = self.vertices;
+        let [uv_a, uv_b, uv_c] = self.uvs;
+
+        let duv_ac = (uv_a.0 - uv_c.0, uv_a.1 - uv_c.1);
+        let duv_bc = (uv_b.0 - uv_c.0, uv_b.1 - uv_c.1);
+        let determinant = duv_ac.0 * duv_bc.1 - duv_ac.1 * duv_bc.0;
+
+        // Degenerate texture coordinates fall back to an arbitrary frame around the normal
+        if determinant.abs() < 1e-8 {
+            let onb = Onb::from_w(normal);
+            return (
+                onb.local(Vec3::new(1.0, 0.0, 0.0)),
+                onb.local(Vec3::new(0.0, 1.0, 0.0)),
+            );
+        }
+
+        let dp_ac = a - c;
+        let dp_bc = b - c;
+        let tangent = (duv_bc.1 * dp_ac - duv_ac.1 * dp_bc) / determinant;
+        let bitangent = (duv_ac.0 * dp_bc - duv_bc.0 * dp_ac) / determinant;
+
+        (tangent, bitangent)
+    }
+}
+
+impl<M: Material> Hittable for Triangle<M> {
+    fn hit(&self, ray: &Ray, time_min: f32, time_max: f32) -> Option<HitRecord> {
+        // Möller–Trumbore intersection
+        let [a, b, c] = self.vertices;
+        let edge_1 = b - a;
+        let edge_2 = c - a;
+
+        let p = ray.direction().cross(edge_2);
+        let determinant = edge_1.dot(p);
+        if determinant.abs() < 1e-8 {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let s = ray.origin() - a;
+        let beta = s.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        let q = s.cross(edge_1);
+        let gamma = ray.direction().dot(q) * inverse_determinant;
+        if gamma < 0.0 || beta + gamma > 1.0 {
+            return None;
+        }
+
+        let t = edge_2.dot(q) * inverse_determinant;
+        if t < time_min || t > time_max {
+            return None;
+        }
+
+        let alpha = 1.0 - beta - gamma;
+        let [uv_a, uv_b, uv_c] = self.uvs;
+        let outward_normal = edge_1.cross(edge_2).normalize();
+        let (tangent, bitangent) = self.calculate_tangents(outward_normal);
+        let mut hit_record = HitRecord {
+            point: ray.at(t),
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent,
+            bitangent,
+            t,
+            u: alpha * uv_a.0 + beta * uv_b.0 + gamma * uv_c.0,
+            v: alpha * uv_a.1 + beta * uv_b.1 + gamma * uv_c.1,
+            front_face: false,
+            material: &self.material,
+            medium_interface: None,
+        };
+
+        hit_record.set_face_normal(ray, outward_normal);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self, _time_start: f32, _time_end: f32) -> Option<Aabb> {
+        let [a, b, c] = self.vertices;
+        let padding = Vec3::new(0.0001, 0.0001, 0.0001);
+        Some(Aabb::new(
+            Vec3::new(
+                a.x.min(b.x).min(c.x),
+                a.y.min(b.y).min(c.y),
+                a.z.min(b.z).min(c.z),
+            ) - padding,
+            Vec3::new(
+                a.x.max(b.x).max(c.x),
+                a.y.max(b.y).max(c.y),
+                a.z.max(b.z).max(c.z),
+            ) + padding,
+        ))
+    }
+
+    fn count(&self) -> u32 {
+        1
+    }
+
+    fn sample_surface(&self, _time: f32) -> Option<HitRecord> {
+        // Uniform barycentric coordinates by folding the unit square onto the triangle
+        let mut rand = sampler::rng();
+        let root = rand.gen::<f32>().sqrt();
+        let beta = root * (1.0 - rand.gen::<f32>());
+        let gamma = root - beta;
+        let alpha = 1.0 - beta - gamma;
+
+        let [a, b, c] = self.vertices;
+        let [uv_a, uv_b, uv_c] = self.uvs;
+        let normal = (b - a).cross(c - a).normalize();
+        let (tangent, bitangent) = self.calculate_tangents(normal);
+        Some(HitRecord {
+            point: alpha * a + beta * b + gamma * c,
+            normal,
+            tangent,
+            bitangent,
+            t: 0.0,
+            u: alpha * uv_a.0 + beta * uv_b.0 + gamma * uv_c.0,
+            v: alpha * uv_a.1 + beta * uv_b.1 + gamma * uv_c.1,
+            front_face: true,
+            material: &self.material,
+            medium_interface: None,
+        })
+    }
+
+    fn area(&self) -> f32 {
+        let [a, b, c] = self.vertices;
+        0.5 * (b - a).cross(c - a).magnitude()
+    }
+}
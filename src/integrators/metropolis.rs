@@ -0,0 +1,289 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    integrators::ProgressiveIntegrator,
+    math::{self, Vec3},
+    ray::Ray,
+    sampler::{self, PrimarySampleSpace},
+    world::World,
+};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{sync::Arc, thread};
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct MetropolisSettings {
+    pub(crate) bootstrap_samples: u32,
+    pub(crate) chains: u32,
+    pub(crate) large_step_probability: f32,
+    pub(crate) small_step_sigma: f32,
+}
+
+struct Chain {
+    space: PrimarySampleSpace,
+    rand: StdRng,
+    pixel: usize,
+    radiance: Vec3,
+}
+
+/// Primary sample space Metropolis light transport on top of the path tracer passed as
+/// `ray_color`, every pass performs one mutation per pixel on average.
+pub(crate) struct Metropolis {
+    camera: Camera,
+    world: Arc<World>,
+    width: u32,
+    height: u32,
+    depth: u32,
+    settings: MetropolisSettings,
+    ray_color: fn(&Ray, &World, u32) -> Vec3,
+    seed: u64,
+    chains: Option<Vec<Chain>>,
+    normalization: f32,
+    film: Film,
+    mutations: u64,
+}
+
+impl Metropolis {
+    pub(crate) fn new(
+        camera: Camera,
+        world: Arc<World>,
+        width: u32,
+        height: u32,
+        depth: u32,
+        settings: MetropolisSettings,
+        ray_color: fn(&Ray, &World, u32) -> Vec3,
+    ) -> Self {
+        Self {
+            camera,
+            world,
+            width,
+            height,
+            depth,
+            settings,
+            ray_color,
+            seed: rand::thread_rng().gen(),
+            chains: None,
+            normalization: 0.0,
+            film: Film::new((width * height) as usize),
+            mutations: 0,
+        }
+    }
+
+    fn primary_sample_space(&self, index: u64) -> PrimarySampleSpace {
+        PrimarySampleSpace::new(
+            self.seed.wrapping_add(index),
+            self.settings.large_step_probability,
+            self.settings.small_step_sigma,
+        )
+    }
+
+    fn sample_path(&self) -> (usize, Vec3) {
+        let mut rand = sampler::rng();
+        let x = rand.gen::<f32>() * self.width as f32;
+        let y = rand.gen::<f32>() * self.height as f32;
+        let pixel = (y as u32).min(self.height - 1) * self.width + (x as u32).min(self.width - 1);
+
+        let ray = self.camera.get_ray(
+            x / (self.width as f32 - 1.0),
+            y / (self.height as f32 - 1.0),
+        );
+
+        let radiance = (self.ray_color)(&ray, &self.world, self.depth);
+        (pixel as usize, radiance)
+    }
+
+    fn contribution(radiance: Vec3) -> f32 {
+        let luminance = math::luminance(radiance);
+        if luminance.is_finite() {
+            luminance.max(0.0)
+        } else {
+            0.0
+        }
+    }
+
+    fn bootstrap(&self, thread_count: usize) -> (f32, Vec<Chain>) {
+        let sample_count = self.settings.bootstrap_samples as u64;
+        let chunk_size = sample_count.div_ceil(thread_count as u64).max(1);
+
+        let weights: Vec<f32> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..sample_count)
+                .step_by(chunk_size as usize)
+                .map(|start| {
+                    scope.spawn(move || {
+                        (start..(start + chunk_size).min(sample_count))
+                            .map(|index| {
+                                let space = self.primary_sample_space(index);
+                                let (_, (_, radiance)) =
+                                    sampler::with_primary_samples(space, || self.sample_path());
+                                Self::contribution(radiance)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let total_weight: f32 = weights.iter().sum();
+        if total_weight <= 0.0 {
+            log::warn!("Bootstrap found no light carrying paths, the image will stay black");
+            return (0.0, Vec::new());
+        }
+
+        let mut distribution = Vec::with_capacity(weights.len());
+        weights.iter().fold(0.0, |sum, weight| {
+            distribution.push((sum + weight) / total_weight);
+            sum + weight
+        });
+
+        let mut rand = StdRng::seed_from_u64(self.seed);
+        let chains = (0..self.settings.chains)
+            .map(|_| {
+                let sample = rand.gen::<f32>();
+                let index = distribution
+                    .partition_point(|&value| value <= sample)
+                    .min(distribution.len() - 1);
+
+                let space = self.primary_sample_space(index as u64);
+                let (mut space, (pixel, radiance)) =
+                    sampler::with_primary_samples(space, || self.sample_path());
+
+                // Chains starting from the same path would otherwise mutate in lockstep
+                space.reseed(rand.gen());
+
+                Chain {
+                    space,
+                    rand: StdRng::seed_from_u64(rand.gen()),
+                    pixel,
+                    radiance,
+                }
+            })
+            .collect();
+
+        (total_weight / sample_count as f32, chains)
+    }
+
+    fn mutate(&self, chain: Chain, mutations: u64) -> Chain {
+        let Chain {
+            mut space,
+            mut rand,
+            mut pixel,
+            mut radiance,
+        } = chain;
+
+        for _ in 0..mutations {
+            space.start_iteration();
+
+            let (proposed_space, (proposed_pixel, proposed_radiance)) =
+                sampler::with_primary_samples(space, || self.sample_path());
+            space = proposed_space;
+
+            let contribution = Self::contribution(radiance);
+            let proposed_contribution = Self::contribution(proposed_radiance);
+            let acceptance = if contribution > 0.0 {
+                (proposed_contribution / contribution).min(1.0)
+            } else {
+                1.0
+            };
+
+            // Splat both states weighted by their expected share to lower the variance
+            if acceptance > 0.0 {
+                self.film.add(
+                    proposed_pixel,
+                    proposed_radiance * (acceptance / proposed_contribution),
+                );
+            }
+
+            if acceptance < 1.0 {
+                self.film
+                    .add(pixel, radiance * ((1.0 - acceptance) / contribution));
+            }
+
+            if rand.gen::<f32>() < acceptance {
+                pixel = proposed_pixel;
+                radiance = proposed_radiance;
+                space.accept();
+            } else {
+                space.reject();
+            }
+        }
+
+        Chain {
+            space,
+            rand,
+            pixel,
+            radiance,
+        }
+    }
+}
+
+impl ProgressiveIntegrator for Metropolis {
+    fn render_pass(&mut self) {
+        let thread_count = thread::available_parallelism().map_or(1, |count| count.get());
+
+        let chains = match self.chains.take() {
+            Some(chains) => chains,
+            None => {
+                let (normalization, chains) = self.bootstrap(thread_count);
+                self.normalization = normalization;
+                chains
+            }
+        };
+
+        if chains.is_empty() {
+            self.chains = Some(chains);
+            return;
+        }
+
+        let chain_count = chains.len() as u64;
+        let mutation_count = (self.width * self.height) as u64;
+        let chunk_size = chains.len().div_ceil(thread_count);
+
+        let mut chains = chains.into_iter().enumerate();
+        let chains = thread::scope(|scope| {
+            let this = &*self;
+            let handles: Vec<_> = (0..thread_count)
+                .map(|_| {
+                    let group: Vec<_> = chains.by_ref().take(chunk_size).collect();
+                    scope.spawn(move || {
+                        group
+                            .into_iter()
+                            .map(|(index, chain)| {
+                                let mutations = mutation_count / chain_count
+                                    + u64::from((index as u64) < mutation_count % chain_count);
+                                this.mutate(chain, mutations)
+                            })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        self.chains = Some(chains);
+        self.mutations += mutation_count;
+    }
+
+    fn image(&self) -> Vec<Vec3> {
+        let pixel_count = (self.width * self.height) as usize;
+        let mutations_per_pixel = self.mutations.max(1) as f32 / pixel_count as f32;
+        let scale = self.normalization / mutations_per_pixel;
+        (0..pixel_count)
+            .map(|index| self.film.pixel(index) * scale)
+            .collect()
+    }
+}
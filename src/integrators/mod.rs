@@ -0,0 +1,19 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+pub(crate) mod metropolis;
+pub(crate) mod path_tracer;
+pub(crate) mod photon_mapper;
+
+/// Integrators which refine the whole image at once instead of rendering it tile by tile.
+pub(crate) trait ProgressiveIntegrator: Send {
+    fn render_pass(&mut self);
+
+    /// Current estimate of the image in linear color, row by row from the bottom.
+    fn image(&self) -> Vec<Vec3>;
+}
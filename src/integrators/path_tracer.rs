@@ -0,0 +1,288 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    math::{self, Vec3},
+    media::{self, Medium},
+    ray::Ray,
+    sampler,
+    spectrum::{ColorSpace, Rgb, SampledWavelengths},
+    world::World,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use rand::Rng;
+
+pub(crate) fn ray_color(ray: &Ray, world: &World, depth: u32) -> Vec3 {
+    trace(ray, world, None, None, depth, &mut Rgb)
+}
+
+/// Traces a path carrying a set of sampled wavelengths and converts it back to RGB.
+pub(crate) fn spectral_ray_color(ray: &Ray, world: &World, depth: u32) -> Vec3 {
+    let mut wavelengths = SampledWavelengths::sample(sampler::rng().gen());
+    let radiance = trace(ray, world, None, None, depth, &mut wavelengths);
+    wavelengths.to_rgb(radiance)
+}
+
+/// Density a ray was scattered with at the last interaction which also sampled lights.
+#[derive(Clone, Copy)]
+struct Scattering {
+    pdf: f32,
+    origin: Vec3,
+}
+
+/// `scattering` is `None` for camera rays and specular bounces which light samples can't
+/// reproduce.
+fn trace<'a, C: ColorSpace>(
+    ray: &Ray,
+    world: &'a World,
+    medium: Option<&'a dyn Medium>,
+    scattering: Option<Scattering>,
+    depth: u32,
+    color_space: &mut C,
+) -> C::Radiance {
+    if depth == 0 {
+        return color_space.radiance(Vec3::new(0.0, 0.0, 0.0));
+    }
+
+    let ray = ray.with_wavelength(color_space.wavelength());
+    let hit_record = world.objects().hit(&ray, 0.001, f32::INFINITY);
+    let (hit_record, transmittance) = media::next_interaction(medium, &ray, hit_record);
+    let transmittance = color_space.radiance(transmittance);
+    let Some(hit_record) = hit_record else {
+        let environment = weight_emission(
+            world,
+            &ray,
+            f32::INFINITY,
+            None,
+            scattering,
+            world.environment(&ray),
+        );
+        let background = color_space.radiance(world.background() + environment);
+        return transmittance.mul_element_wise(background);
+    };
+
+    let emitted = weight_emission(
+        world,
+        &ray,
+        hit_record.t,
+        hit_record.material.light(),
+        scattering,
+        hit_record.material.emitted(&ray, &hit_record),
+    );
+    let emitted = color_space.radiance(emitted);
+    let direct = sample_light(&ray, &hit_record, world, medium, color_space) + emitted;
+    let Some(scatter_record) = hit_record.material.scatter(&ray, &hit_record) else {
+        return transmittance.mul_element_wise(direct);
+    };
+
+    color_space.scattered(&scatter_record);
+
+    let direction = scatter_record.ray.direction();
+    let scattering = if hit_record.material.is_interface() {
+        // Shadow rays cross interfaces, so light behind them still competes with the samples
+        // taken before
+        scattering
+    } else {
+        let samples_lights = !scatter_record.specular && hit_record.material.samples_lights();
+        samples_lights.then(|| Scattering {
+            pdf: hit_record.material.pdf(&ray, &hit_record, direction),
+            origin: hit_record.point,
+        })
+    };
+    let medium = media::next_medium(medium, &hit_record, direction);
+    let ray_color = trace(
+        &scatter_record.ray,
+        world,
+        medium,
+        scattering,
+        depth - 1,
+        color_space,
+    );
+    let attenuation = color_space.radiance(scatter_record.attenuation);
+    transmittance.mul_element_wise(attenuation.mul_element_wise(ray_color) + direct)
+}
+
+/// Weights emission found by a scattered ray against the light samples of the previous
+/// interaction which could have found it as well. `light` is the index of the light the
+/// ray hit, if it's known.
+fn weight_emission(
+    world: &World,
+    ray: &Ray,
+    time_max: f32,
+    light: Option<usize>,
+    scattering: Option<Scattering>,
+    emitted: Vec3,
+) -> Vec3 {
+    let Some(scattering) = scattering else {
+        return emitted;
+    };
+
+    if math::near_zero(emitted) {
+        return emitted;
+    }
+
+    // Interfaces pass the ray on unchanged, so the light was sampled from further back on
+    // the same line
+    let light_ray = Ray::new(scattering.origin, ray.direction(), ray.time());
+    let time_max = if time_max.is_finite() {
+        (ray.at(time_max) - scattering.origin).magnitude() / ray.direction().magnitude()
+    } else {
+        time_max
+    };
+
+    let light_pdf = light_pdf(world, &light_ray, time_max, light);
+    if light_pdf <= 0.0 {
+        return emitted;
+    }
+
+    emitted * power_heuristic(scattering.pdf, light_pdf)
+}
+
+/// Next event estimation, connects the interaction to a light picked by its estimated
+/// contribution and weights the sample against scattering into the same direction.
+fn sample_light<'a, C: ColorSpace>(
+    ray: &Ray,
+    hit_record: &HitRecord<'a>,
+    world: &'a World,
+    medium: Option<&'a dyn Medium>,
+    color_space: &C,
+) -> C::Radiance {
+    let black = color_space.radiance(Vec3::new(0.0, 0.0, 0.0));
+    if !hit_record.material.samples_lights() {
+        return black;
+    }
+
+    let Some((index, probability)) = world
+        .light_sampler()
+        .sample(hit_record.point, sampler::rng().gen::<f32>())
+    else {
+        return black;
+    };
+
+    let light = &world.lights()[index];
+    let Some(sample) = light.sample_li(hit_record.point, ray.time()) else {
+        return black;
+    };
+
+    if sample.pdf <= 0.0 || math::near_zero(sample.radiance) {
+        return black;
+    }
+
+    let scattering = hit_record
+        .material
+        .evaluate(ray, hit_record, sample.direction);
+    if math::near_zero(scattering) {
+        return black;
+    }
+
+    let shadow_ray =
+        Ray::new(hit_record.point, sample.direction, ray.time()).with_wavelength(ray.wavelength());
+    let medium = media::next_medium(medium, hit_record, sample.direction);
+    let visibility = visibility(&shadow_ray, sample.distance, world, medium);
+    if math::near_zero(visibility) {
+        return black;
+    }
+
+    let weight = if light.is_delta() {
+        1.0
+    } else {
+        power_heuristic(
+            probability * sample.pdf,
+            hit_record.material.pdf(ray, hit_record, sample.direction),
+        )
+    };
+
+    let radiance = sample.radiance * (weight / (probability * sample.pdf));
+    color_space
+        .radiance(scattering)
+        .mul_element_wise(color_space.radiance(visibility))
+        .mul_element_wise(color_space.radiance(radiance))
+}
+
+/// Transmittance along a shadow ray up to `distance`. Invisible media boundaries are
+/// crossed and volumes attenuate the ray by their transmittance, while surfaces and
+/// scattering inside the medium of the path block it.
+fn visibility<'a>(
+    ray: &Ray,
+    distance: f32,
+    world: &'a World,
+    mut medium: Option<&'a dyn Medium>,
+) -> Vec3 {
+    let mut ray = *ray;
+    let mut remaining = distance - 0.001;
+    let mut visibility = Vec3::new(1.0, 1.0, 1.0);
+    while remaining > 0.001 {
+        let hit_record = world.objects().hit_surface(&ray, 0.001, remaining);
+        let time_max = hit_record
+            .as_ref()
+            .map_or(remaining, |hit_record| hit_record.t);
+        visibility *= world.objects().transmittance(&ray, 0.001, time_max);
+        if math::near_zero(visibility) {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        if let Some(medium) = medium {
+            let (event, weight) = medium.sample(&ray, time_max);
+            if event.is_some() {
+                return Vec3::new(0.0, 0.0, 0.0);
+            }
+
+            visibility.mul_assign_element_wise(weight);
+        }
+
+        let Some(hit_record) = hit_record else {
+            break;
+        };
+
+        if !hit_record.material.is_interface() {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        medium = media::next_medium(medium, &hit_record, ray.direction());
+        remaining -= hit_record.t;
+        ray = Ray::new(hit_record.point, ray.direction(), ray.time())
+            .with_wavelength(ray.wavelength());
+    }
+
+    visibility
+}
+
+/// Density of the light samples taken at the origin of the ray reaching the position it
+/// hits at `time_max`, including the probability of picking the light. Only lights at
+/// infinity are visible to rays leaving the scene, and a known light is the only one at
+/// the hit.
+fn light_pdf(world: &World, ray: &Ray, time_max: f32, light: Option<usize>) -> f32 {
+    let light_sampler = world.light_sampler();
+    let pdf = |index: usize| {
+        let pdf = world.lights()[index].pdf_li(ray, time_max);
+        if pdf <= 0.0 {
+            return 0.0;
+        }
+
+        light_sampler.pmf(ray.origin(), index) * pdf
+    };
+
+    if time_max.is_infinite() {
+        return light_sampler
+            .unbounded_lights()
+            .iter()
+            .copied()
+            .map(pdf)
+            .sum();
+    }
+
+    match light {
+        Some(index) => pdf(index),
+        None => (0..world.lights().len()).map(pdf).sum(),
+    }
+}
+
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let squared = pdf * pdf;
+    squared / (squared + other_pdf * other_pdf)
+}
@@ -0,0 +1,405 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    camera::Camera,
+    film::Film,
+    hit_record::HitRecord,
+    integrators::ProgressiveIntegrator,
+    materials::ScatterRecord,
+    math::{self, Vec3},
+    media::{self, Medium},
+    ray::Ray,
+    sampler,
+    world::World,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use rand::Rng;
+use std::{
+    collections::HashMap,
+    f32::consts::PI,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+struct Pixel {
+    radius: f32,
+    photons: f32,
+    flux: Vec3,
+    direct: Vec3,
+}
+
+struct VisiblePoint<'a> {
+    ray: Ray,
+    hit_record: HitRecord<'a>,
+    throughput: Vec3,
+}
+
+struct PhotonGrid {
+    cell_size: f32,
+    cells: HashMap<(i32, i32, i32), Vec<usize>>,
+}
+
+impl PhotonGrid {
+    fn new(visible_points: &[Option<VisiblePoint>], pixels: &[Pixel]) -> Self {
+        let max_radius = visible_points
+            .iter()
+            .zip(pixels)
+            .filter(|(visible_point, _)| visible_point.is_some())
+            .fold(0.0f32, |radius, (_, pixel)| radius.max(pixel.radius));
+
+        let mut grid = Self {
+            cell_size: 2.0 * max_radius,
+            cells: HashMap::new(),
+        };
+
+        for (index, (visible_point, pixel)) in visible_points.iter().zip(pixels).enumerate() {
+            let Some(visible_point) = visible_point else {
+                continue;
+            };
+
+            let radius = Vec3::new(pixel.radius, pixel.radius, pixel.radius);
+            let minimum = grid.cell(visible_point.hit_record.point - radius);
+            let maximum = grid.cell(visible_point.hit_record.point + radius);
+            for x in minimum.0..=maximum.0 {
+                for y in minimum.1..=maximum.1 {
+                    for z in minimum.2..=maximum.2 {
+                        grid.cells.entry((x, y, z)).or_default().push(index);
+                    }
+                }
+            }
+        }
+
+        grid
+    }
+
+    fn cell(&self, point: Vec3) -> (i32, i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+            (point.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn candidates(&self, point: Vec3) -> &[usize] {
+        self.cells
+            .get(&self.cell(point))
+            .map_or(&[], |indices| indices.as_slice())
+    }
+}
+
+/// Stochastic progressive photon mapping, every pass traces one camera path per pixel up to
+/// its first diffuse hit and gathers the photons of a fresh photon pass around it.
+pub(crate) struct PhotonMapper {
+    camera: Camera,
+    world: Arc<World>,
+    width: u32,
+    height: u32,
+    depth: u32,
+    photon_count: u32,
+    pass: u32,
+    pixels: Vec<Pixel>,
+    light_distribution: Vec<f32>,
+}
+
+impl PhotonMapper {
+    // Fraction of the new photons kept by the progressive radius reduction
+    const ALPHA: f32 = 2.0 / 3.0;
+
+    pub(crate) fn new(
+        camera: Camera,
+        world: Arc<World>,
+        width: u32,
+        height: u32,
+        depth: u32,
+        photon_count: u32,
+        initial_radius: f32,
+    ) -> Self {
+        let pixels = (0..(width * height))
+            .map(|_| Pixel {
+                radius: initial_radius,
+                photons: 0.0,
+                flux: Vec3::new(0.0, 0.0, 0.0),
+                direct: Vec3::new(0.0, 0.0, 0.0),
+            })
+            .collect();
+
+        let mut total_power = 0.0;
+        let mut light_distribution: Vec<f32> = world
+            .lights()
+            .iter()
+            .map(|light| {
+                total_power += light.power();
+                total_power
+            })
+            .collect();
+        light_distribution
+            .iter_mut()
+            .for_each(|value| *value /= total_power);
+
+        if light_distribution.is_empty() {
+            log::warn!("Scene contains no lights, photon mapping will only show direct emission");
+        }
+
+        Self {
+            camera,
+            world,
+            width,
+            height,
+            depth,
+            photon_count,
+            pass: 0,
+            pixels,
+            light_distribution,
+        }
+    }
+
+    /// Whether photons are gathered at the hit instead of following the scattered ray.
+    fn gathers(scatter_record: &ScatterRecord, hit_record: &HitRecord) -> bool {
+        !scatter_record.specular
+            && !math::near_zero(hit_record.normal)
+            && hit_record.material.samples_lights()
+    }
+
+    fn trace_camera_path<'a>(
+        &self,
+        world: &'a World,
+        index: usize,
+    ) -> (Option<VisiblePoint<'a>>, Vec3) {
+        let mut rand = sampler::rng();
+        let x = index as u32 % self.width;
+        let y = index as u32 / self.width;
+        let u = (x as f32 + rand.gen::<f32>()) / (self.width as f32 - 1.0);
+        let v = (y as f32 + rand.gen::<f32>()) / (self.height as f32 - 1.0);
+
+        let mut ray = self.camera.get_ray(u, v);
+        let mut medium: Option<&dyn Medium> = None;
+        let mut throughput = Vec3::new(1.0, 1.0, 1.0);
+        let mut direct = Vec3::new(0.0, 0.0, 0.0);
+        for _ in 0..self.depth {
+            let hit_record = world.objects().hit(&ray, 0.001, f32::INFINITY);
+            let (hit_record, transmittance) = media::next_interaction(medium, &ray, hit_record);
+            throughput.mul_assign_element_wise(transmittance);
+            let Some(hit_record) = hit_record else {
+                direct += throughput.mul_element_wise(world.background() + world.environment(&ray));
+                break;
+            };
+
+            let emitted = hit_record.material.emitted(&ray, &hit_record);
+            direct += throughput.mul_element_wise(emitted);
+
+            let Some(scatter_record) = hit_record.material.scatter(&ray, &hit_record) else {
+                break;
+            };
+
+            // Participating media report no normal and materials not sampling lights have no
+            // exact evaluate for the gather, walk through both like through glass
+            if Self::gathers(&scatter_record, &hit_record) {
+                return (
+                    Some(VisiblePoint {
+                        ray,
+                        hit_record,
+                        throughput,
+                    }),
+                    direct,
+                );
+            }
+
+            throughput.mul_assign_element_wise(scatter_record.attenuation);
+            medium = media::next_medium(medium, &hit_record, scatter_record.ray.direction());
+            ray = scatter_record.ray;
+        }
+
+        (None, direct)
+    }
+
+    fn trace_photon(
+        &self,
+        world: &World,
+        visible_points: &[Option<VisiblePoint>],
+        grid: &PhotonGrid,
+        gathered: &Film,
+        counts: &[AtomicU32],
+    ) {
+        let mut rand = sampler::rng();
+
+        let sample = rand.gen::<f32>();
+        let light_index = self
+            .light_distribution
+            .partition_point(|&value| value < sample)
+            .min(self.light_distribution.len() - 1);
+        let light_probability = self.light_distribution[light_index]
+            - light_index
+                .checked_sub(1)
+                .map_or(0.0, |previous| self.light_distribution[previous]);
+
+        let Some((mut ray, flux)) = world.lights()[light_index].emit() else {
+            return;
+        };
+
+        let mut medium: Option<&dyn Medium> = None;
+        let mut throughput = flux / light_probability;
+        for _ in 0..self.depth {
+            let hit_record = world.objects().hit(&ray, 0.001, f32::INFINITY);
+            let (hit_record, transmittance) = media::next_interaction(medium, &ray, hit_record);
+            throughput.mul_assign_element_wise(transmittance);
+            let Some(hit_record) = hit_record else {
+                break;
+            };
+
+            if throughput.x.max(throughput.y).max(throughput.z) <= 0.0 {
+                break;
+            }
+
+            let Some(scatter_record) = hit_record.material.scatter(&ray, &hit_record) else {
+                break;
+            };
+
+            if Self::gathers(&scatter_record, &hit_record) {
+                let incoming = -ray.direction().normalize();
+                for &index in grid.candidates(hit_record.point) {
+                    let Some(visible_point) = &visible_points[index] else {
+                        continue;
+                    };
+
+                    let radius = self.pixels[index].radius;
+                    let offset = visible_point.hit_record.point - hit_record.point;
+                    if offset.dot(offset) > radius * radius {
+                        continue;
+                    }
+
+                    // Photons from behind the surface count for transmitting materials,
+                    // evaluate leaves out the lobes a material does not have
+                    let cosine = visible_point.hit_record.normal.dot(incoming).abs();
+                    if cosine <= 0.0 {
+                        continue;
+                    }
+
+                    let scattering = visible_point.hit_record.material.evaluate(
+                        &visible_point.ray,
+                        &visible_point.hit_record,
+                        incoming,
+                    ) / cosine;
+                    gathered.add(index, scattering.mul_element_wise(throughput));
+                    counts[index].fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            let attenuated = throughput.mul_element_wise(scatter_record.attenuation);
+
+            // Russian roulette keeps the photon flux roughly constant along the path
+            let survival = (attenuated.x.max(attenuated.y).max(attenuated.z)
+                / throughput.x.max(throughput.y).max(throughput.z))
+            .min(1.0);
+            if survival <= 0.0 || rand.gen::<f32>() > survival {
+                break;
+            }
+
+            throughput = attenuated / survival;
+            medium = media::next_medium(medium, &hit_record, scatter_record.ray.direction());
+            ray = scatter_record.ray;
+        }
+    }
+}
+
+impl ProgressiveIntegrator for PhotonMapper {
+    fn render_pass(&mut self) {
+        let world = self.world.clone();
+        let thread_count = thread::available_parallelism().map_or(1, |count| count.get());
+        let pixel_count = self.pixels.len();
+        let chunk_size = pixel_count.div_ceil(thread_count);
+
+        let camera_paths: Vec<(Option<VisiblePoint>, Vec3)> = thread::scope(|scope| {
+            let this = &*self;
+            let world = &*world;
+            let handles: Vec<_> = (0..pixel_count)
+                .step_by(chunk_size)
+                .map(|start| {
+                    scope.spawn(move || {
+                        (start..(start + chunk_size).min(pixel_count))
+                            .map(|index| this.trace_camera_path(world, index))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        });
+
+        let (visible_points, direct): (Vec<_>, Vec<_>) = camera_paths.into_iter().unzip();
+        let gathered = Film::new(pixel_count);
+        let counts: Vec<AtomicU32> = (0..pixel_count).map(|_| AtomicU32::new(0)).collect();
+
+        if !self.light_distribution.is_empty() {
+            let grid = PhotonGrid::new(&visible_points, &self.pixels);
+            thread::scope(|scope| {
+                let this = &*self;
+                let world = &*world;
+                let visible_points = &visible_points;
+                let grid = &grid;
+                let gathered = &gathered;
+                let counts = &counts;
+                for thread_index in 0..thread_count as u32 {
+                    let photon_count = self.photon_count / thread_count as u32
+                        + u32::from(thread_index < self.photon_count % thread_count as u32);
+                    scope.spawn(move || {
+                        for _ in 0..photon_count {
+                            this.trace_photon(world, visible_points, grid, gathered, counts);
+                        }
+                    });
+                }
+            });
+        }
+
+        for (index, ((pixel, visible_point), direct)) in self
+            .pixels
+            .iter_mut()
+            .zip(&visible_points)
+            .zip(direct)
+            .enumerate()
+        {
+            pixel.direct += direct;
+
+            let count = counts[index].load(Ordering::Relaxed) as f32;
+            let Some(visible_point) = visible_point else {
+                continue;
+            };
+
+            if count == 0.0 {
+                continue;
+            }
+
+            let photons = pixel.photons + Self::ALPHA * count;
+            let radius = pixel.radius * (photons / (pixel.photons + count)).sqrt();
+            let flux = visible_point
+                .throughput
+                .mul_element_wise(gathered.pixel(index));
+            pixel.flux = (pixel.flux + flux) * (radius * radius) / (pixel.radius * pixel.radius);
+            pixel.photons = photons;
+            pixel.radius = radius;
+        }
+
+        self.pass += 1;
+    }
+
+    fn image(&self) -> Vec<Vec3> {
+        let passes = self.pass.max(1) as f32;
+        let photons = passes * self.photon_count as f32;
+        self.pixels
+            .iter()
+            .map(|pixel| {
+                pixel.direct / passes + pixel.flux / (photons * PI * pixel.radius * pixel.radius)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,187 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    hittable::Hittable,
+    lights::{light_sampler::LightBounds, Light, LightSample},
+    math::{self, Vec3},
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+pub(crate) struct AreaLight {
+    shape: Box<dyn Hittable>,
+    power: f32,
+    // Normal and emitting faces of flat shapes, curved ones emit into every direction
+    orientation: Option<(Vec3, bool, bool)>,
+}
+
+impl AreaLight {
+    const POWER_SAMPLES: u32 = 64;
+
+    pub(crate) fn new(shape: Box<dyn Hittable>) -> Self {
+        let mut faces = [Vec3::new(0.0, 0.0, 0.0); 2];
+        let mut normal = None;
+        let mut planar = true;
+        for _ in 0..Self::POWER_SAMPLES {
+            let Some(record) = shape.sample_surface(0.0) else {
+                continue;
+            };
+
+            let first_normal = *normal.get_or_insert(record.normal);
+            planar &= record.normal.dot(first_normal) > 0.999;
+
+            for (face, front_face) in faces.iter_mut().zip([true, false]) {
+                let direction = Self::sample_direction(&record, front_face);
+                *face += Self::radiance(&record, front_face, direction, 0.0);
+            }
+        }
+
+        let radiance = (faces[0] + faces[1]) / Self::POWER_SAMPLES as f32;
+
+        // Cosine weighted directions cancel the cosine of the emitted power down to pi
+        let power = math::luminance(radiance) * shape.area() * PI;
+
+        let orientation = normal.filter(|_| planar).map(|normal| {
+            let front = math::luminance(faces[0]) > 0.0;
+            let back = math::luminance(faces[1]) > 0.0;
+            (normal, front, back)
+        });
+
+        Self {
+            shape,
+            power,
+            orientation,
+        }
+    }
+
+    fn sample_direction(record: &HitRecord, front_face: bool) -> Vec3 {
+        let normal = if front_face {
+            record.normal
+        } else {
+            -record.normal
+        };
+
+        let direction = normal + math::random_unit_vector();
+        if math::near_zero(direction) {
+            normal
+        } else {
+            direction
+        }
+    }
+
+    /// Radiance leaving a sampled surface point from the given face into `direction`.
+    fn radiance(record: &HitRecord, front_face: bool, direction: Vec3, time: f32) -> Vec3 {
+        let hit_record = HitRecord {
+            normal: if front_face {
+                record.normal
+            } else {
+                -record.normal
+            },
+            front_face,
+            ..*record
+        };
+
+        let ray = Ray::new(record.point + direction, -direction, time);
+        record.material.emitted(&ray, &hit_record)
+    }
+}
+
+impl Light for AreaLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        let mut rand = sampler::rng();
+        let time = rand.gen::<f32>();
+        let record = self.shape.sample_surface(time)?;
+
+        // Pick the face by the radiance it emits along its normal
+        let front = math::luminance(Self::radiance(&record, true, record.normal, time));
+        let back = math::luminance(Self::radiance(&record, false, -record.normal, time));
+        if front + back <= 0.0 {
+            return None;
+        }
+
+        let front_probability = front / (front + back);
+        let (front_face, probability) = if rand.gen::<f32>() < front_probability {
+            (true, front_probability)
+        } else {
+            (false, 1.0 - front_probability)
+        };
+
+        let direction = Self::sample_direction(&record, front_face);
+        let radiance = Self::radiance(&record, front_face, direction, time);
+        let flux = radiance * self.shape.area() * PI / probability;
+
+        Some((Ray::new(record.point, direction, time), flux))
+    }
+
+    fn power(&self) -> f32 {
+        self.power
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = self.shape.bounding_box(0.0, 1.0)?;
+        let light_bounds = match self.orientation {
+            Some((normal, true, false)) => {
+                LightBounds::new(bounds, normal, self.power, 1.0, 0.0, false)
+            }
+            Some((normal, false, true)) => {
+                LightBounds::new(bounds, -normal, self.power, 1.0, 0.0, false)
+            }
+            Some((normal, _, _)) => LightBounds::new(bounds, normal, self.power, 1.0, 0.0, true),
+            None => LightBounds::omnidirectional(bounds, self.power),
+        };
+
+        Some(light_bounds)
+    }
+
+    fn register(&self, index: usize) {
+        if let Some(record) = self.shape.sample_surface(0.0) {
+            record.material.set_light(index);
+        }
+    }
+
+    fn sample_li(&self, point: Vec3, time: f32) -> Option<LightSample> {
+        let direction = self.shape.random_direction(point, time)?;
+        let ray = Ray::new(point, direction, time);
+        let record = self.shape.hit_deterministic(&ray, 0.001, f32::INFINITY)?;
+
+        // Light passing through a stochastic first hit is left to BSDF sampling
+        let record = self.shape.hit(&ray, 0.001, record.t + 0.001)?;
+        let pdf = self.shape.pdf_value(point, direction, time);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        // The hit faces the point like any ray finding the light, textures included
+        let distance = record.t * direction.magnitude();
+        Some(LightSample {
+            direction: direction.normalize(),
+            distance,
+            radiance: record.material.emitted(&ray, &record),
+            pdf,
+        })
+    }
+
+    fn pdf_li(&self, ray: &Ray, time_max: f32) -> f32 {
+        // Parts of the light further along the ray are hidden behind the hit it ended at,
+        // and light found behind the first hit of the light is never sampled
+        let Some(record) = self.shape.hit_deterministic(ray, 0.001, time_max + 0.001) else {
+            return 0.0;
+        };
+
+        if record.t < time_max - 0.001 {
+            return 0.0;
+        }
+
+        self.shape
+            .pdf_value(ray.origin(), ray.direction(), ray.time())
+    }
+}
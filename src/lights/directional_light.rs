@@ -0,0 +1,76 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb,
+    lights::{Light, LightSample},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Infinitely distant light like the sun, arriving from a single direction everywhere in
+/// the scene. `direction` is the direction the light travels in.
+pub(crate) struct DirectionalLight {
+    direction: Vec3,
+    irradiance: Vec3,
+    center: Vec3,
+    radius: f32,
+}
+
+impl DirectionalLight {
+    pub(crate) fn new(direction: Vec3, irradiance: Vec3) -> Self {
+        Self {
+            direction: direction.normalize(),
+            irradiance,
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 0.0,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        if self.radius <= 0.0 {
+            return None;
+        }
+
+        // Photons start on a disk covering the bounding sphere of the scene
+        let disk = Onb::from_w(self.direction).local(math::random_in_unit_disk());
+        let origin = self.center + (disk - self.direction) * self.radius;
+        let flux = self.irradiance * (PI * self.radius * self.radius);
+
+        let time = sampler::rng().gen::<f32>();
+        Some((Ray::new(origin, self.direction, time), flux))
+    }
+
+    fn power(&self) -> f32 {
+        math::luminance(self.irradiance) * PI * self.radius * self.radius
+    }
+
+    fn sample_li(&self, _point: Vec3, _time: f32) -> Option<LightSample> {
+        Some(LightSample {
+            direction: -self.direction,
+            distance: f32::INFINITY,
+            radiance: self.irradiance,
+            pdf: 1.0,
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+
+    fn preprocess(&mut self, bounds: &Aabb) {
+        self.center = (bounds.minimum() + bounds.maximum()) / 2.0;
+        self.radius = (bounds.maximum() - self.center).magnitude();
+    }
+}
@@ -0,0 +1,220 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb,
+    distribution::Distribution2D,
+    lights::{Light, LightSample},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use image::{codecs::hdr::HdrDecoder, ImageResult};
+use rand::Rng;
+use std::{f32::consts::PI, fs::File, io::BufReader, path::Path};
+
+/// Infinitely distant light surrounding the scene, given as an equirectangular HDR image
+/// with the y axis pointing up. Directions are importance sampled by the luminance of the
+/// pixels, so small and bright regions like the sun are found by light samples.
+pub(crate) struct EnvironmentLight {
+    pixels: Vec<Vec3>,
+    width: usize,
+    height: usize,
+    distribution: Distribution2D,
+    rotation: f32,
+    intensity: f32,
+    center: Vec3,
+    radius: f32,
+}
+
+impl EnvironmentLight {
+    /// Loads Radiance `.hdr` files or any other format the image crate reads, like `.exr`.
+    pub(crate) fn load(path: &str) -> ImageResult<Self> {
+        let is_radiance = Path::new(path)
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("hdr"));
+
+        let (width, height, pixels): (usize, usize, Vec<Vec3>) = if is_radiance {
+            // The generic loader tone maps Radiance files down to 8 bits
+            let decoder = HdrDecoder::new(BufReader::new(File::open(path)?))?;
+            let metadata = decoder.metadata();
+            let pixels = decoder
+                .read_image_hdr()?
+                .into_iter()
+                .map(|pixel| Vec3::new(pixel[0], pixel[1], pixel[2]))
+                .collect();
+            (metadata.width as usize, metadata.height as usize, pixels)
+        } else {
+            let image = image::open(path)?.into_rgb32f();
+            let pixels = image
+                .pixels()
+                .map(|pixel| Vec3::new(pixel[0], pixel[1], pixel[2]))
+                .collect();
+            (image.width() as usize, image.height() as usize, pixels)
+        };
+
+        Ok(Self::new(pixels, width, height))
+    }
+
+    /// Environment from rows of pixels, the first row lies straight up.
+    pub(crate) fn new(pixels: Vec<Vec3>, width: usize, height: usize) -> Self {
+        // Rows near the poles cover less solid angle than their pixel count suggests
+        let mut values = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let sin_theta = ((y as f32 + 0.5) / height as f32 * PI).sin();
+            for x in 0..width {
+                values.push(math::luminance(pixels[y * width + x]) * sin_theta);
+            }
+        }
+
+        Self {
+            pixels,
+            width,
+            height,
+            distribution: Distribution2D::new(&values, width, height),
+            rotation: 0.0,
+            intensity: 1.0,
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 0.0,
+        }
+    }
+
+    /// Environment evaluating `radiance` for the direction of every pixel.
+    pub(crate) fn from_fn(width: usize, height: usize, radiance: impl Fn(Vec3) -> Vec3) -> Self {
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(radiance(Self::map_direction(
+                    (x as f32 + 0.5) / width as f32,
+                    (y as f32 + 0.5) / height as f32,
+                    0.0,
+                )));
+            }
+        }
+
+        Self::new(pixels, width, height)
+    }
+
+    /// Turns the map around the y axis by `rotation` degrees.
+    pub(crate) fn with_rotation(self, rotation: f32) -> Self {
+        Self {
+            rotation: rotation.to_radians(),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_intensity(self, intensity: f32) -> Self {
+        Self { intensity, ..self }
+    }
+
+    fn map_direction(u: f32, v: f32, rotation: f32) -> Vec3 {
+        let theta = v * PI;
+        let phi = 2.0 * PI * u - PI + rotation;
+        Vec3::new(
+            theta.sin() * phi.cos(),
+            theta.cos(),
+            theta.sin() * phi.sin(),
+        )
+    }
+
+    fn coordinates(&self, direction: Vec3) -> (f32, f32) {
+        let direction = direction.normalize();
+        let theta = direction.y.clamp(-1.0, 1.0).acos();
+        let phi = direction.z.atan2(direction.x) - self.rotation;
+        (((phi + PI) / (2.0 * PI)).rem_euclid(1.0), theta / PI)
+    }
+
+    fn lookup(&self, u: f32, v: f32) -> Vec3 {
+        let x = ((u * self.width as f32) as usize).min(self.width - 1);
+        let y = ((v * self.height as f32) as usize).min(self.height - 1);
+        self.pixels[y * self.width + x] * self.intensity
+    }
+
+    /// Converts the density over the image to one over solid angle.
+    fn direction_pdf(&self, image_pdf: f32, v: f32) -> f32 {
+        let sin_theta = (v * PI).sin();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+
+        image_pdf / (2.0 * PI * PI * sin_theta)
+    }
+
+    /// Samples a direction the light arrives from together with its density.
+    fn sample_direction(&self) -> Option<(Vec3, f32)> {
+        let mut rand = sampler::rng();
+        let ((u, v), image_pdf) = self.distribution.sample(rand.gen(), rand.gen());
+        let pdf = self.direction_pdf(image_pdf, v);
+        (pdf > 0.0).then(|| (Self::map_direction(u, v, self.rotation), pdf))
+    }
+}
+
+impl Light for EnvironmentLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        if self.radius <= 0.0 {
+            return None;
+        }
+
+        // Photons start on a disk covering the bounding sphere, facing the sampled direction
+        let (direction, pdf) = self.sample_direction()?;
+        let disk = Onb::from_w(direction).local(math::random_in_unit_disk());
+        let origin = self.center + (direction + disk) * self.radius;
+
+        let (u, v) = self.coordinates(direction);
+        let area = PI * self.radius * self.radius;
+        let flux = self.lookup(u, v) * (area / pdf);
+
+        let time = sampler::rng().gen::<f32>();
+        Some((Ray::new(origin, -direction, time), flux))
+    }
+
+    fn power(&self) -> f32 {
+        // Radiance integrated over the sphere of directions, hitting the disk of the scene
+        let solid_angle = 2.0 * PI * PI / (self.width * self.height) as f32;
+        let radiance = (0..self.height)
+            .map(|y| {
+                let sin_theta = ((y as f32 + 0.5) / self.height as f32 * PI).sin();
+                let row = &self.pixels[y * self.width..(y + 1) * self.width];
+                row.iter().map(|pixel| math::luminance(*pixel)).sum::<f32>() * sin_theta
+            })
+            .sum::<f32>();
+
+        radiance * solid_angle * self.intensity * PI * self.radius * self.radius
+    }
+
+    fn sample_li(&self, _point: Vec3, _time: f32) -> Option<LightSample> {
+        let (direction, pdf) = self.sample_direction()?;
+        let (u, v) = self.coordinates(direction);
+        Some(LightSample {
+            direction,
+            distance: f32::INFINITY,
+            radiance: self.lookup(u, v),
+            pdf,
+        })
+    }
+
+    fn pdf_li(&self, ray: &Ray, time_max: f32) -> f32 {
+        if time_max.is_finite() {
+            return 0.0;
+        }
+
+        let (u, v) = self.coordinates(ray.direction());
+        self.direction_pdf(self.distribution.pdf(u, v), v)
+    }
+
+    fn environment(&self, direction: Vec3) -> Vec3 {
+        let (u, v) = self.coordinates(direction);
+        self.lookup(u, v)
+    }
+
+    fn preprocess(&mut self, bounds: &Aabb) {
+        self.center = (bounds.minimum() + bounds.maximum()) / 2.0;
+        self.radius = (bounds.maximum() - self.center).magnitude();
+    }
+}
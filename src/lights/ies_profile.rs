@@ -0,0 +1,300 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+use cgmath::InnerSpace;
+use std::{
+    f32::consts::PI,
+    fs,
+    io::{self, ErrorKind},
+};
+
+/// Photometric profile of a luminaire read from an IES LM-63 file, assuming the common
+/// type C photometry. Directions are given in a frame where the z axis points to the
+/// nadir at a vertical angle of zero and the x axis to the horizontal angle of zero.
+/// Intensities are normalized to a peak of one.
+#[derive(Clone, Debug)]
+pub(crate) struct IesProfile {
+    vertical_angles: Vec<f32>,
+    horizontal_angles: Vec<f32>,
+    // Candela values per horizontal angle, each row holding all vertical angles
+    candela: Vec<f32>,
+    average: f32,
+}
+
+impl IesProfile {
+    pub(crate) fn load(path: &str) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(text: &str) -> io::Result<Self> {
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_owned());
+
+        // Keywords precede the tilt line, all photometric data follows it
+        let mut lines = text.lines();
+        let tilt = lines
+            .by_ref()
+            .map(str::trim)
+            .find(|line| line.starts_with("TILT="))
+            .ok_or_else(|| invalid("missing tilt line of the IES profile"))?;
+
+        let mut values = lines
+            .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .parse::<f32>()
+                    .map_err(|_| invalid("invalid number in the IES profile"))
+            });
+        let mut next = || {
+            values
+                .next()
+                .unwrap_or_else(|| Err(invalid("truncated IES profile")))
+        };
+
+        // Tilt data only matters for lamps mounted at an angle, it is skipped
+        if tilt == "TILT=INCLUDE" {
+            next()?;
+            let count = next()? as usize;
+            for _ in 0..2 * count {
+                next()?;
+            }
+        } else if tilt != "TILT=NONE" {
+            return Err(invalid("tilt files of IES profiles aren't supported"));
+        }
+
+        let _lamps = next()?;
+        let _lumens = next()?;
+        let multiplier = next()?;
+        let vertical_count = next()? as usize;
+        let horizontal_count = next()? as usize;
+        let photometric_type = next()?;
+        for _ in 0..4 {
+            // Units and the dimensions of the luminous opening
+            next()?;
+        }
+        let ballast_factor = next()?;
+        for _ in 0..2 {
+            // Reserved value and input watts
+            next()?;
+        }
+
+        if photometric_type != 1.0 {
+            return Err(invalid(
+                "only type C photometry of IES profiles is supported",
+            ));
+        }
+
+        if vertical_count == 0 || horizontal_count == 0 {
+            return Err(invalid("IES profile without angles"));
+        }
+
+        let mut read = |count: usize| (0..count).map(|_| next()).collect::<io::Result<Vec<_>>>();
+        let vertical_angles = read(vertical_count)?;
+        let horizontal_angles = read(horizontal_count)?;
+        let mut candela = read(vertical_count * horizontal_count)?;
+
+        let is_sorted = |angles: &[f32]| angles.windows(2).all(|pair| pair[0] < pair[1]);
+        if !is_sorted(&vertical_angles) || !is_sorted(&horizontal_angles) {
+            return Err(invalid("unsorted angles in the IES profile"));
+        }
+
+        let scale = multiplier * ballast_factor;
+        candela
+            .iter_mut()
+            .for_each(|value| *value = (*value * scale).max(0.0));
+        let peak = candela.iter().copied().fold(0.0, f32::max);
+        if peak > 0.0 {
+            candela.iter_mut().for_each(|value| *value /= peak);
+        }
+
+        let mut profile = Self {
+            vertical_angles,
+            horizontal_angles,
+            candela,
+            average: 0.0,
+        };
+        profile.average = profile.compute_average();
+
+        Ok(profile)
+    }
+
+    /// Average intensity over the sphere of directions.
+    pub(crate) fn average(&self) -> f32 {
+        self.average
+    }
+
+    /// Relative intensity emitted into a direction of the local frame.
+    pub(crate) fn intensity(&self, direction: Vec3) -> f32 {
+        let length = direction.magnitude();
+        if length <= 0.0 {
+            return 0.0;
+        }
+
+        let vertical = (direction.z / length).clamp(-1.0, 1.0).acos().to_degrees();
+        let horizontal = direction.y.atan2(direction.x).to_degrees();
+        self.lookup(vertical, self.fold_horizontal(horizontal))
+    }
+
+    /// Maps a horizontal angle into the range covered by the file using its symmetry.
+    fn fold_horizontal(&self, angle: f32) -> f32 {
+        let mut angle = angle.rem_euclid(360.0);
+        let last = self.horizontal_angles[self.horizontal_angles.len() - 1];
+        if last <= 90.0 {
+            if angle > 180.0 {
+                angle = 360.0 - angle;
+            }
+
+            if angle > 90.0 {
+                angle = 180.0 - angle;
+            }
+        } else if last <= 180.0 && angle > 180.0 {
+            angle = 360.0 - angle;
+        }
+
+        angle
+    }
+
+    /// Bilinear interpolation over the angle grid, directions outside of the vertical
+    /// range receive no light.
+    fn lookup(&self, vertical: f32, horizontal: f32) -> f32 {
+        let Some((v, v_weight)) = Self::locate(&self.vertical_angles, vertical) else {
+            return 0.0;
+        };
+
+        // A single horizontal angle describes a rotationally symmetric luminaire
+        let (h, h_weight) = Self::locate(&self.horizontal_angles, horizontal).unwrap_or((0, 0.0));
+
+        let vertical_count = self.vertical_angles.len();
+        let value = |h: usize, v: usize| {
+            let h = h.min(self.horizontal_angles.len() - 1);
+            let v = v.min(vertical_count - 1);
+            self.candela[h * vertical_count + v]
+        };
+
+        let lower = value(h, v) * (1.0 - v_weight) + value(h, v + 1) * v_weight;
+        let upper = value(h + 1, v) * (1.0 - v_weight) + value(h + 1, v + 1) * v_weight;
+        lower * (1.0 - h_weight) + upper * h_weight
+    }
+
+    /// Index of the segment containing `angle` and the position inside of it.
+    fn locate(angles: &[f32], angle: f32) -> Option<(usize, f32)> {
+        let first = angles[0];
+        let last = angles[angles.len() - 1];
+        if angles.len() == 1 || angle < first || angle > last {
+            return None;
+        }
+
+        let index = angles
+            .partition_point(|&value| value <= angle)
+            .saturating_sub(1)
+            .min(angles.len() - 2);
+        let width = angles[index + 1] - angles[index];
+        Some((index, ((angle - angles[index]) / width).clamp(0.0, 1.0)))
+    }
+
+    fn compute_average(&self) -> f32 {
+        const THETA_STEPS: usize = 90;
+        const PHI_STEPS: usize = 180;
+
+        let mut total = 0.0;
+        for i in 0..THETA_STEPS {
+            let theta = (i as f32 + 0.5) / THETA_STEPS as f32 * PI;
+            for j in 0..PHI_STEPS {
+                let phi = (j as f32 + 0.5) / PHI_STEPS as f32 * 2.0 * PI;
+                let direction = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                );
+                total += self.intensity(direction) * theta.sin();
+            }
+        }
+
+        // Cells cover pi / THETA_STEPS by 2 pi / PHI_STEPS of the 4 pi sphere
+        total * PI * 2.0 * PI / (THETA_STEPS * PHI_STEPS) as f32 / (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Quadrant symmetric type C profile with three vertical and two horizontal angles.
+    fn profile(tilt: &str, photometric_type: u32) -> String {
+        format!(
+            "IESNA:LM-63-2002\n\
+             [TEST] quadrant\n\
+             {tilt}\n\
+             1 1000 1 3 2 {photometric_type} 1 0 0 0\n\
+             1 1 100\n\
+             0 45 90\n\
+             0 90\n\
+             100 80 0\n\
+             50 40 0\n"
+        )
+    }
+
+    /// Direction at the vertical and horizontal angle of the profile, in degrees.
+    fn direction(vertical: f32, horizontal: f32) -> Vec3 {
+        let (vertical, horizontal) = (vertical.to_radians(), horizontal.to_radians());
+        Vec3::new(
+            vertical.sin() * horizontal.cos(),
+            vertical.sin() * horizontal.sin(),
+            vertical.cos(),
+        )
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "intensity {actual} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn parses_without_tilt() {
+        let profile = IesProfile::parse(&profile("TILT=NONE", 1)).unwrap();
+        assert_close(profile.intensity(direction(0.0, 0.0)), 1.0);
+        assert_close(profile.intensity(direction(45.0, 0.0)), 0.8);
+        assert_close(profile.intensity(direction(45.0, 90.0)), 0.4);
+        assert_close(profile.intensity(direction(45.0, 45.0)), 0.6);
+        assert_close(profile.intensity(direction(120.0, 0.0)), 0.0);
+    }
+
+    #[test]
+    fn skips_included_tilt() {
+        let tilt = "TILT=INCLUDE\n1\n3\n0 45 90\n1 0.9 0.8";
+        let included = IesProfile::parse(&profile(tilt, 1)).unwrap();
+        let plain = IesProfile::parse(&profile("TILT=NONE", 1)).unwrap();
+        for (vertical, horizontal) in [(0.0, 0.0), (30.0, 20.0), (60.0, 75.0)] {
+            assert_close(
+                included.intensity(direction(vertical, horizontal)),
+                plain.intensity(direction(vertical, horizontal)),
+            );
+        }
+    }
+
+    #[test]
+    fn folds_quadrant_symmetry() {
+        let profile = IesProfile::parse(&profile("TILT=NONE", 1)).unwrap();
+        for (folded, horizontal) in [(90.0, 270.0), (0.0, 180.0), (45.0, 135.0), (30.0, 330.0)] {
+            assert_close(
+                profile.intensity(direction(45.0, horizontal)),
+                profile.intensity(direction(45.0, folded)),
+            );
+        }
+
+        assert_close(profile.intensity(direction(45.0, 270.0)), 0.4);
+    }
+
+    #[test]
+    fn rejects_type_b_photometry() {
+        let error = IesProfile::parse(&profile("TILT=NONE", 2)).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
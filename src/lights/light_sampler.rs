@@ -0,0 +1,444 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{aabb::Aabb, lights::Light, math::Vec3};
+
+use cgmath::InnerSpace;
+use std::f32::consts::PI;
+
+/// Spatial and directional extent of the light leaving a light source. Normals of the
+/// emitting surface lie in a cone of `cos_theta_o` around `direction`, and light leaves
+/// each normal within `cos_theta_e` of it.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct LightBounds {
+    bounds: Aabb,
+    direction: Vec3,
+    power: f32,
+    cos_theta_o: f32,
+    cos_theta_e: f32,
+    two_sided: bool,
+}
+
+impl LightBounds {
+    pub(crate) fn new(
+        bounds: Aabb,
+        direction: Vec3,
+        power: f32,
+        cos_theta_o: f32,
+        cos_theta_e: f32,
+        two_sided: bool,
+    ) -> Self {
+        Self {
+            bounds,
+            direction: direction.normalize(),
+            power,
+            cos_theta_o,
+            cos_theta_e,
+            two_sided,
+        }
+    }
+
+    /// Bounds of a light emitting into every direction from within `bounds`.
+    pub(crate) fn omnidirectional(bounds: Aabb, power: f32) -> Self {
+        Self::new(bounds, Vec3::new(0.0, 0.0, 1.0), power, -1.0, 0.0, false)
+    }
+
+    fn centroid(&self) -> Vec3 {
+        (self.bounds.minimum() + self.bounds.maximum()) / 2.0
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        if self.power <= 0.0 {
+            return *other;
+        }
+
+        if other.power <= 0.0 {
+            return *self;
+        }
+
+        let (direction, cos_theta_o) = Self::union_cones(
+            (self.direction, self.cos_theta_o),
+            (other.direction, other.cos_theta_o),
+        );
+
+        Self {
+            bounds: Aabb::surrounding_box(self.bounds, other.bounds),
+            direction,
+            power: self.power + other.power,
+            cos_theta_o,
+            cos_theta_e: self.cos_theta_e.min(other.cos_theta_e),
+            two_sided: self.two_sided || other.two_sided,
+        }
+    }
+
+    /// Smallest cone containing both cones, following the construction of pbrt.
+    fn union_cones(a: (Vec3, f32), b: (Vec3, f32)) -> (Vec3, f32) {
+        let full_sphere = (a.0, -1.0);
+        let theta_a = a.1.clamp(-1.0, 1.0).acos();
+        let theta_b = b.1.clamp(-1.0, 1.0).acos();
+        let theta_d = a.0.dot(b.0).clamp(-1.0, 1.0).acos();
+
+        if (theta_d + theta_b).min(PI) <= theta_a {
+            return a;
+        }
+
+        if (theta_d + theta_a).min(PI) <= theta_b {
+            return b;
+        }
+
+        let theta_o = (theta_a + theta_d + theta_b) / 2.0;
+        if theta_o >= PI {
+            return full_sphere;
+        }
+
+        let axis = a.0.cross(b.0);
+        if axis.magnitude2() < 1e-12 {
+            return full_sphere;
+        }
+
+        // Rotate the axis of the first cone towards the second one
+        let axis = axis.normalize();
+        let angle = theta_o - theta_a;
+        let direction = a.0 * angle.cos()
+            + axis.cross(a.0) * angle.sin()
+            + axis * axis.dot(a.0) * (1.0 - angle.cos());
+
+        (direction.normalize(), theta_o.cos())
+    }
+
+    /// Conservative estimate of the light arriving at `point`, after the importance
+    /// function of the light BVH by Conty Estevez and Kulla.
+    fn importance(&self, point: Vec3) -> f32 {
+        if self.power <= 0.0 {
+            return 0.0;
+        }
+
+        let centroid = self.centroid();
+        let offset = point - centroid;
+        let diagonal = (self.bounds.maximum() - self.bounds.minimum()).magnitude();
+        let distance_squared = offset.magnitude2().max(diagonal / 2.0);
+        if distance_squared <= 0.0 {
+            return self.power;
+        }
+
+        let mut cos_theta_w = self.direction.dot(offset.normalize());
+        if self.two_sided {
+            cos_theta_w = cos_theta_w.abs();
+        }
+
+        // Angle subtended by the bounding sphere of the box seen from the point
+        let radius_squared = diagonal * diagonal / 4.0;
+        let cos_theta_b = if offset.magnitude2() < radius_squared {
+            -1.0
+        } else {
+            (1.0 - radius_squared / offset.magnitude2()).max(0.0).sqrt()
+        };
+
+        // Smallest angle between the normal cone and the direction to the point
+        let cos_theta_x = Self::cos_subtract(cos_theta_w, self.cos_theta_o);
+        let cos_theta_p = Self::cos_subtract(cos_theta_x, cos_theta_b);
+        if cos_theta_p <= self.cos_theta_e {
+            return 0.0;
+        }
+
+        self.power * cos_theta_p / distance_squared
+    }
+
+    /// Cosine of the difference of two angles clamped at zero.
+    fn cos_subtract(cos_a: f32, cos_b: f32) -> f32 {
+        if cos_a > cos_b {
+            return 1.0;
+        }
+
+        let sin_a = (1.0 - cos_a * cos_a).max(0.0).sqrt();
+        let sin_b = (1.0 - cos_b * cos_b).max(0.0).sqrt();
+        cos_a * cos_b + sin_a * sin_b
+    }
+}
+
+enum Node {
+    Leaf(usize),
+    /// The first child directly follows the node, the second one lies at the index.
+    Interior(usize),
+}
+
+/// Picks lights proportional to their estimated contribution at a point by walking a BVH
+/// over the bounds of the lights. Lights without bounds, like those at infinity, share
+/// their probability evenly with the whole hierarchy.
+pub(crate) struct LightSampler {
+    nodes: Vec<(LightBounds, Node)>,
+    unbounded: Vec<usize>,
+    // Branches taken from the root to every bounded light, one bit per level
+    trails: Vec<Option<u64>>,
+}
+
+impl LightSampler {
+    pub(crate) fn new(lights: &[Box<dyn Light>]) -> Self {
+        let mut bounded = Vec::new();
+        let mut unbounded = Vec::new();
+        for (index, light) in lights.iter().enumerate() {
+            match light.bounds() {
+                Some(bounds) if bounds.power > 0.0 => bounded.push((index, bounds)),
+                Some(_) => {}
+                None => unbounded.push(index),
+            }
+        }
+
+        let mut sampler = Self {
+            nodes: Vec::new(),
+            unbounded,
+            trails: vec![None; lights.len()],
+        };
+
+        if !bounded.is_empty() {
+            sampler.build(&mut bounded, 0, 0);
+        }
+
+        sampler
+    }
+
+    fn build(
+        &mut self,
+        lights: &mut [(usize, LightBounds)],
+        trail: u64,
+        depth: u32,
+    ) -> LightBounds {
+        if lights.len() == 1 || depth == 63 {
+            let (index, bounds) = lights[0];
+            self.nodes.push((bounds, Node::Leaf(index)));
+            self.trails[index] = Some(trail);
+            return bounds;
+        }
+
+        let centroids = lights
+            .iter()
+            .map(|(_, bounds)| Aabb::new(bounds.centroid(), bounds.centroid()))
+            .reduce(Aabb::surrounding_box)
+            .unwrap();
+        let extent = centroids.maximum() - centroids.minimum();
+        let axis = if extent.x > extent.y && extent.x > extent.z {
+            0
+        } else if extent.y > extent.z {
+            1
+        } else {
+            2
+        };
+
+        lights.sort_by(|a, b| a.1.centroid()[axis].total_cmp(&b.1.centroid()[axis]));
+        let (first, second) = lights.split_at_mut(lights.len() / 2);
+
+        let node = self.nodes.len();
+        self.nodes.push((first[0].1, Node::Leaf(first[0].0)));
+        let first_bounds = self.build(first, trail, depth + 1);
+        let second_node = self.nodes.len();
+        let second_bounds = self.build(second, trail | (1 << depth), depth + 1);
+
+        let bounds = first_bounds.union(&second_bounds);
+        self.nodes[node] = (bounds, Node::Interior(second_node));
+        bounds
+    }
+
+    /// Indices of the lights without bounds, the only ones rays leaving the scene can see.
+    pub(crate) fn unbounded_lights(&self) -> &[usize] {
+        &self.unbounded
+    }
+
+    fn unbounded_probability(&self) -> f32 {
+        let shares = self.unbounded.len() + usize::from(!self.nodes.is_empty());
+        if shares == 0 {
+            return 0.0;
+        }
+
+        self.unbounded.len() as f32 / shares as f32
+    }
+
+    /// Picks a light for `point` from a uniform `u`, returning its index and probability.
+    pub(crate) fn sample(&self, point: Vec3, u: f32) -> Option<(usize, f32)> {
+        let unbounded_probability = self.unbounded_probability();
+        if u < unbounded_probability {
+            let count = self.unbounded.len();
+            let index = ((u / unbounded_probability * count as f32) as usize).min(count - 1);
+            return Some((self.unbounded[index], unbounded_probability / count as f32));
+        }
+
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut u = ((u - unbounded_probability) / (1.0 - unbounded_probability)).min(0.999_999);
+        let mut probability = 1.0 - unbounded_probability;
+        let mut node = 0;
+        loop {
+            match self.nodes[node].1 {
+                Node::Leaf(index) => {
+                    let importance = self.nodes[node].0.importance(point);
+                    return (importance > 0.0).then_some((index, probability));
+                }
+                Node::Interior(second) => {
+                    let first_importance = self.nodes[node + 1].0.importance(point);
+                    let second_importance = self.nodes[second].0.importance(point);
+                    let total = first_importance + second_importance;
+                    if total <= 0.0 {
+                        return None;
+                    }
+
+                    // Probabilities are computed like in `pmf` so that both agree exactly
+                    let first_probability = first_importance / total;
+                    if u < first_probability {
+                        u /= first_probability;
+                        probability *= first_probability;
+                        node += 1;
+                    } else {
+                        u = (u - first_probability) / (1.0 - first_probability);
+                        probability *= second_importance / total;
+                        node = second;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Probability of `sample` picking the light at `index` for `point`.
+    pub(crate) fn pmf(&self, point: Vec3, index: usize) -> f32 {
+        let unbounded_probability = self.unbounded_probability();
+        let Some(trail) = self.trails[index] else {
+            if self.unbounded.contains(&index) {
+                return unbounded_probability / self.unbounded.len() as f32;
+            }
+
+            return 0.0;
+        };
+
+        let mut probability = 1.0 - unbounded_probability;
+        let mut node = 0;
+        let mut depth = 0;
+        while let Node::Interior(second) = self.nodes[node].1 {
+            let first_importance = self.nodes[node + 1].0.importance(point);
+            let second_importance = self.nodes[second].0.importance(point);
+            let total = first_importance + second_importance;
+            if total <= 0.0 {
+                return 0.0;
+            }
+
+            if trail & (1 << depth) == 0 {
+                probability *= first_importance / total;
+                node += 1;
+            } else {
+                probability *= second_importance / total;
+                node = second;
+            }
+
+            depth += 1;
+        }
+
+        if self.nodes[node].0.importance(point) > 0.0 {
+            probability
+        } else {
+            0.0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lights::LightSample, ray::Ray};
+
+    struct MockLight {
+        bounds: Option<LightBounds>,
+    }
+
+    impl Light for MockLight {
+        fn emit(&self) -> Option<(Ray, Vec3)> {
+            None
+        }
+
+        fn power(&self) -> f32 {
+            self.bounds.map_or(0.0, |bounds| bounds.power)
+        }
+
+        fn sample_li(&self, _point: Vec3, _time: f32) -> Option<LightSample> {
+            None
+        }
+
+        fn bounds(&self) -> Option<LightBounds> {
+            self.bounds
+        }
+    }
+
+    fn bounded(center: Vec3, power: f32, direction: Option<Vec3>) -> Box<dyn Light> {
+        let offset = Vec3::new(0.25, 0.25, 0.25);
+        let bounds = Aabb::new(center - offset, center + offset);
+        let bounds = match direction {
+            Some(direction) => LightBounds::new(bounds, direction, power, 1.0, 0.0, false),
+            None => LightBounds::omnidirectional(bounds, power),
+        };
+
+        Box::new(MockLight {
+            bounds: Some(bounds),
+        })
+    }
+
+    fn unbounded() -> Box<dyn Light> {
+        Box::new(MockLight { bounds: None })
+    }
+
+    fn points() -> [Vec3; 4] {
+        [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(3.0, 1.0, -2.0),
+            Vec3::new(-5.0, 4.0, 6.0),
+            Vec3::new(0.5, -3.0, 0.5),
+        ]
+    }
+
+    #[test]
+    fn sample_matches_pmf() {
+        let mut lights = vec![unbounded()];
+        for i in 0..12 {
+            let i = i as f32;
+            let center = Vec3::new(i.sin() * 4.0, i * 0.5 - 3.0, i.cos() * 4.0);
+            let direction = (i as u32 % 3 != 0).then(|| Vec3::new(i.cos(), 1.0, -i.sin()));
+            lights.push(bounded(center, 1.0 + i, direction));
+        }
+        lights.push(unbounded());
+
+        let sampler = LightSampler::new(&lights);
+        for point in points() {
+            for step in 0..1000 {
+                let u = step as f32 / 1000.0;
+                let Some((index, probability)) = sampler.sample(point, u) else {
+                    continue;
+                };
+
+                assert!(probability > 0.0);
+                assert_eq!(
+                    probability,
+                    sampler.pmf(point, index),
+                    "light {index} at {u}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pmf_sums_to_one() {
+        let mut lights = vec![unbounded(), unbounded()];
+        for i in 0..9 {
+            let i = i as f32;
+            let center = Vec3::new(i * 1.5 - 6.0, (i * 2.0).sin(), (i * 3.0).cos() * 2.0);
+            lights.push(bounded(center, 0.5 + i * i, None));
+        }
+
+        let sampler = LightSampler::new(&lights);
+        for point in points() {
+            let total: f32 = (0..lights.len())
+                .map(|index| sampler.pmf(point, index))
+                .sum();
+            assert!((total - 1.0).abs() < 1e-5, "probabilities sum to {total}");
+        }
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{aabb::Aabb, lights::light_sampler::LightBounds, math::Vec3, ray::Ray};
+
+pub(crate) mod area_light;
+pub(crate) mod directional_light;
+pub(crate) mod environment_light;
+pub(crate) mod ies_profile;
+pub(crate) mod light_sampler;
+pub(crate) mod physical_sky;
+pub(crate) mod point_light;
+pub(crate) mod spot_light;
+pub(crate) mod sun_light;
+
+/// Light arriving at a point from a position sampled on a light.
+pub(crate) struct LightSample {
+    /// Unit direction from the lit point towards the light.
+    pub(crate) direction: Vec3,
+    /// Distance to the sampled position, infinite for distant lights.
+    pub(crate) distance: f32,
+    pub(crate) radiance: Vec3,
+    /// Density over solid angle, delta lights report one.
+    pub(crate) pdf: f32,
+}
+
+pub(crate) trait Light: Send + Sync {
+    /// Emits a photon leaving the light, returning its ray and the flux it carries.
+    fn emit(&self) -> Option<(Ray, Vec3)>;
+
+    /// Total emitted power, used to distribute photons between lights.
+    fn power(&self) -> f32;
+
+    /// Samples the light arriving at `point`, the caller still has to test the visibility.
+    fn sample_li(&self, point: Vec3, time: f32) -> Option<LightSample>;
+
+    /// Density of `sample_li` picking the position the ray hits at `time_max`, zero for
+    /// delta lights and positions off the light.
+    fn pdf_li(&self, _ray: &Ray, _time_max: f32) -> f32 {
+        0.0
+    }
+
+    /// Radiance arriving from `direction` at infinity, seen by rays leaving the scene.
+    fn environment(&self, _direction: Vec3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Spatial and directional extent of the emission, used to pick lights by their
+    /// contribution. Lights at infinity have none.
+    fn bounds(&self) -> Option<LightBounds> {
+        None
+    }
+
+    /// Delta lights have no extent, rays never hit them and only `sample_li` reaches them.
+    fn is_delta(&self) -> bool {
+        false
+    }
+
+    /// Tells the surfaces emitting the light its index in the world.
+    fn register(&self, _index: usize) {}
+
+    /// Called once the scene is assembled, lights outside of it aim at its bounds.
+    fn preprocess(&mut self, _bounds: &Aabb) {}
+}
@@ -0,0 +1,194 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    lights::{environment_light::EnvironmentLight, sun_light::SunLight},
+    math::Vec3,
+    spectrum,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use std::f32::consts::{FRAC_PI_2, PI};
+
+/// Clear sky following the analytic model by Preetham et al., for a sun at `elevation`
+/// above the horizon and `azimuth` measured from the x axis towards the z axis, both in
+/// degrees. The turbidity ranges from 2 on a clear to 10 on a hazy day. The sky and the
+/// sun become separate lights, directions below the horizon see a diffuse ground.
+#[derive(Clone, Debug)]
+pub(crate) struct PhysicalSky {
+    sun_direction: Vec3,
+    turbidity: f32,
+    ground_albedo: Vec3,
+    // Chromaticity x, y and luminance Y at the zenith with their Perez coefficients
+    zenith: [f32; 3],
+    coefficients: [[f32; 5]; 3],
+}
+
+impl PhysicalSky {
+    // Radiance per unit of the model luminance in kcd/m²
+    const LUMINANCE_SCALE: f32 = 0.04;
+    // Luminance of the sun before passing through the atmosphere in kcd/m²
+    const SUN_LUMINANCE: f32 = 2.0e6;
+    const SUN_ANGULAR_RADIUS: f32 = 0.2665;
+    const WIDTH: usize = 512;
+    const HEIGHT: usize = 256;
+
+    pub(crate) fn new(elevation: f32, azimuth: f32, turbidity: f32) -> Self {
+        // The model is only valid with the sun above the horizon
+        let elevation = elevation.clamp(0.0, 90.0).to_radians();
+        let azimuth = azimuth.to_radians();
+        let sun_direction = Vec3::new(
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        );
+
+        let t = turbidity.clamp(1.0, 10.0);
+        let theta = FRAC_PI_2 - elevation;
+        let polynomial = |coefficients: [f32; 4]| {
+            coefficients[0] * theta * theta * theta
+                + coefficients[1] * theta * theta
+                + coefficients[2] * theta
+                + coefficients[3]
+        };
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta);
+        let zenith_luminance = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_x = t * t * polynomial([0.00166, -0.00375, 0.00209, 0.0])
+            + t * polynomial([-0.02903, 0.06377, -0.03202, 0.00394])
+            + polynomial([0.11693, -0.21196, 0.06052, 0.25886]);
+        let zenith_y = t * t * polynomial([0.00275, -0.00610, 0.00317, 0.0])
+            + t * polynomial([-0.04214, 0.08970, -0.04153, 0.00516])
+            + polynomial([0.15346, -0.26756, 0.06670, 0.26688]);
+
+        Self {
+            sun_direction,
+            turbidity: t,
+            ground_albedo: Vec3::new(0.2, 0.2, 0.2),
+            zenith: [zenith_x, zenith_y, zenith_luminance],
+            coefficients: [
+                [
+                    -0.0193 * t - 0.2592,
+                    -0.0665 * t + 0.0008,
+                    -0.0004 * t + 0.2125,
+                    -0.0641 * t - 0.8989,
+                    -0.0033 * t + 0.0452,
+                ],
+                [
+                    -0.0167 * t - 0.2608,
+                    -0.0950 * t + 0.0092,
+                    -0.0079 * t + 0.2102,
+                    -0.0441 * t - 1.6537,
+                    -0.0109 * t + 0.0529,
+                ],
+                [
+                    0.1787 * t - 1.4630,
+                    -0.3554 * t + 0.4275,
+                    -0.0227 * t + 5.3251,
+                    0.1206 * t - 2.5771,
+                    -0.0670 * t + 0.3703,
+                ],
+            ],
+        }
+    }
+
+    pub(crate) fn with_ground_albedo(self, ground_albedo: Vec3) -> Self {
+        Self {
+            ground_albedo,
+            ..self
+        }
+    }
+
+    /// Sky without the sun, tabulated into an environment to importance sample it.
+    pub(crate) fn sky_light(&self) -> EnvironmentLight {
+        let ground = self.ground_radiance();
+        EnvironmentLight::from_fn(Self::WIDTH, Self::HEIGHT, |direction| {
+            if direction.y < 0.0 {
+                ground
+            } else {
+                self.sky_radiance(direction)
+            }
+        })
+    }
+
+    pub(crate) fn sun_light(&self) -> SunLight {
+        SunLight::new(
+            self.sun_direction,
+            self.sun_radiance(),
+            Self::SUN_ANGULAR_RADIUS,
+        )
+    }
+
+    fn perez(coefficients: &[f32; 5], cos_theta: f32, gamma: f32) -> f32 {
+        let [a, b, c, d, e] = *coefficients;
+        (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+    }
+
+    fn sky_radiance(&self, direction: Vec3) -> Vec3 {
+        // The distribution diverges at the horizon
+        let cos_theta = direction.y.max(0.01);
+        let gamma = direction.dot(self.sun_direction).clamp(-1.0, 1.0).acos();
+        let sun_theta = self.sun_direction.y.clamp(-1.0, 1.0).acos();
+
+        let [x, y, luminance] = [0, 1, 2].map(|i| {
+            self.zenith[i] * Self::perez(&self.coefficients[i], cos_theta, gamma)
+                / Self::perez(&self.coefficients[i], 1.0, sun_theta)
+        });
+
+        let xyz = Vec3::new(x / y * luminance, luminance, (1.0 - x - y) / y * luminance);
+        spectrum::xyz_to_srgb(xyz).map(|channel| channel.max(0.0)) * Self::LUMINANCE_SCALE
+    }
+
+    /// Sun attenuated by Rayleigh and aerosol scattering along its path through the air,
+    /// evaluated at the wavelengths of the color channels.
+    fn sun_radiance(&self) -> Vec3 {
+        let zenith_angle = self.sun_direction.y.clamp(0.0, 1.0).acos();
+        let air_mass =
+            1.0 / (zenith_angle.cos() + 0.15 * (93.885 - zenith_angle.to_degrees()).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+
+        let transmittance = |wavelength: f32| {
+            let rayleigh = (-0.008735 * wavelength.powf(-4.08) * air_mass).exp();
+            let aerosol = (-beta * wavelength.powf(-1.3) * air_mass).exp();
+            rayleigh * aerosol
+        };
+
+        Vec3::new(
+            transmittance(0.65),
+            transmittance(0.55),
+            transmittance(0.45),
+        ) * (Self::SUN_LUMINANCE * Self::LUMINANCE_SCALE)
+    }
+
+    /// Diffuse ground lit by the sun and the sky above it.
+    fn ground_radiance(&self) -> Vec3 {
+        const THETA_STEPS: usize = 16;
+        const PHI_STEPS: usize = 32;
+
+        let theta_step = FRAC_PI_2 / THETA_STEPS as f32;
+        let phi_step = 2.0 * PI / PHI_STEPS as f32;
+
+        let mut irradiance = Vec3::new(0.0, 0.0, 0.0);
+        for i in 0..THETA_STEPS {
+            let theta = (i as f32 + 0.5) * theta_step;
+            for j in 0..PHI_STEPS {
+                let phi = (j as f32 + 0.5) * phi_step;
+                let direction = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                irradiance += self.sky_radiance(direction)
+                    * (theta.cos() * theta.sin() * theta_step * phi_step);
+            }
+        }
+
+        let sun_solid_angle = 2.0 * PI * (1.0 - Self::SUN_ANGULAR_RADIUS.to_radians().cos());
+        irradiance += self.sun_radiance() * (sun_solid_angle * self.sun_direction.y.max(0.0));
+
+        self.ground_albedo.mul_element_wise(irradiance) / PI
+    }
+}
@@ -0,0 +1,100 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb,
+    lights::{ies_profile::IesProfile, light_sampler::LightBounds, Light, LightSample},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Light shining equally into all directions from a single point, the intensity is the
+/// power per unit solid angle.
+pub(crate) struct PointLight {
+    position: Vec3,
+    intensity: Vec3,
+    profile: Option<IesProfile>,
+}
+
+impl PointLight {
+    pub(crate) fn new(position: Vec3, intensity: Vec3) -> Self {
+        Self {
+            position,
+            intensity,
+            profile: None,
+        }
+    }
+
+    /// Shapes the emission by a measured profile hanging with its nadir down the negative
+    /// y axis, the intensity then applies to the peak of the profile.
+    pub(crate) fn with_profile(self, profile: IesProfile) -> Self {
+        Self {
+            profile: Some(profile),
+            ..self
+        }
+    }
+
+    /// Intensity emitted into `direction` pointing away from the light.
+    fn intensity(&self, direction: Vec3) -> Vec3 {
+        let Some(profile) = &self.profile else {
+            return self.intensity;
+        };
+
+        let frame = Onb::from_wu(Vec3::new(0.0, -1.0, 0.0), Vec3::new(1.0, 0.0, 0.0));
+        self.intensity * profile.intensity(frame.world_to_local(direction))
+    }
+}
+
+impl Light for PointLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        let time = sampler::rng().gen::<f32>();
+        let direction = math::random_unit_vector();
+        let flux = self.intensity(direction) * (4.0 * PI);
+        Some((Ray::new(self.position, direction, time), flux))
+    }
+
+    fn power(&self) -> f32 {
+        let average = self.profile.as_ref().map_or(1.0, IesProfile::average);
+        math::luminance(self.intensity) * average * 4.0 * PI
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = Aabb::new(self.position, self.position);
+        Some(LightBounds::omnidirectional(bounds, self.power()))
+    }
+
+    fn sample_li(&self, point: Vec3, _time: f32) -> Option<LightSample> {
+        let offset = self.position - point;
+        let distance_squared = offset.magnitude2();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+        let intensity = self.intensity(-direction);
+        if math::near_zero(intensity) {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: intensity / distance_squared,
+            pdf: 1.0,
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,176 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb,
+    lights::{ies_profile::IesProfile, light_sampler::LightBounds, Light, LightSample},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Point light restricted to a cone around the direction to `target`. Both angles are
+/// measured from the axis in degrees, the intensity falls off smoothly from
+/// `falloff_start` to zero at `cone_angle`.
+pub(crate) struct SpotLight {
+    position: Vec3,
+    direction: Vec3,
+    intensity: Vec3,
+    cos_cone: f32,
+    cos_falloff_start: f32,
+    profile: Option<IesProfile>,
+    power: f32,
+}
+
+impl SpotLight {
+    pub(crate) fn new(
+        position: Vec3,
+        target: Vec3,
+        intensity: Vec3,
+        cone_angle: f32,
+        falloff_start: f32,
+    ) -> Self {
+        let cos_cone = cone_angle.to_radians().cos();
+        let mut light = Self {
+            position,
+            direction: (target - position).normalize(),
+            intensity,
+            cos_cone,
+            cos_falloff_start: falloff_start.to_radians().cos().max(cos_cone),
+            profile: None,
+            power: 0.0,
+        };
+        light.power = light.compute_power();
+        light
+    }
+
+    /// Shapes the emission inside the cone by a measured profile with its nadir along the
+    /// axis of the spot, the intensity then applies to the peak of the profile.
+    pub(crate) fn with_profile(self, profile: IesProfile) -> Self {
+        let mut light = Self {
+            profile: Some(profile),
+            ..self
+        };
+        light.power = light.compute_power();
+        light
+    }
+
+    /// Intensity emitted into `direction` pointing away from the light.
+    fn intensity(&self, direction: Vec3) -> Vec3 {
+        let frame = Onb::from_w(self.direction);
+        let local = frame.world_to_local(direction);
+        let profile = self
+            .profile
+            .as_ref()
+            .map_or(1.0, |profile| profile.intensity(local));
+        self.intensity * (self.falloff(local.z) * profile)
+    }
+
+    /// Integrates the intensity over the cone, profiles and the falloff included.
+    fn compute_power(&self) -> f32 {
+        const THETA_STEPS: usize = 64;
+        const PHI_STEPS: usize = 128;
+
+        let frame = Onb::from_w(self.direction);
+        let theta_max = self.cos_cone.clamp(-1.0, 1.0).acos();
+        let mut total = 0.0;
+        for i in 0..THETA_STEPS {
+            let theta = (i as f32 + 0.5) / THETA_STEPS as f32 * theta_max;
+            for j in 0..PHI_STEPS {
+                let phi = (j as f32 + 0.5) / PHI_STEPS as f32 * 2.0 * PI;
+                let direction = frame.local(Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.sin() * phi.sin(),
+                    theta.cos(),
+                ));
+                total += math::luminance(self.intensity(direction)) * theta.sin();
+            }
+        }
+
+        // Cells cover theta_max / THETA_STEPS by 2 pi / PHI_STEPS of the cone
+        total * theta_max * 2.0 * PI / (THETA_STEPS * PHI_STEPS) as f32
+    }
+
+    fn falloff(&self, cosine: f32) -> f32 {
+        if cosine >= self.cos_falloff_start {
+            return 1.0;
+        }
+
+        if cosine <= self.cos_cone {
+            return 0.0;
+        }
+
+        let t = (cosine - self.cos_cone) / (self.cos_falloff_start - self.cos_cone);
+        t * t * (3.0 - 2.0 * t)
+    }
+}
+
+impl Light for SpotLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        let mut rand = sampler::rng();
+
+        // Uniform directions inside the cone
+        let cos_theta = 1.0 - rand.gen::<f32>() * (1.0 - self.cos_cone);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand.gen::<f32>();
+        let direction = Onb::from_w(self.direction).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ));
+
+        let solid_angle = 2.0 * PI * (1.0 - self.cos_cone);
+        let flux = self.intensity(direction) * solid_angle;
+        Some((Ray::new(self.position, direction, rand.gen()), flux))
+    }
+
+    fn power(&self) -> f32 {
+        self.power
+    }
+
+    fn bounds(&self) -> Option<LightBounds> {
+        let bounds = Aabb::new(self.position, self.position);
+        Some(LightBounds::new(
+            bounds,
+            self.direction,
+            self.power(),
+            1.0,
+            self.cos_cone,
+            false,
+        ))
+    }
+
+    fn sample_li(&self, point: Vec3, _time: f32) -> Option<LightSample> {
+        let offset = self.position - point;
+        let distance_squared = offset.magnitude2();
+        if distance_squared <= 0.0 {
+            return None;
+        }
+
+        let distance = distance_squared.sqrt();
+        let direction = offset / distance;
+        let intensity = self.intensity(-direction);
+        if math::near_zero(intensity) {
+            return None;
+        }
+
+        Some(LightSample {
+            direction,
+            distance,
+            radiance: intensity / distance_squared,
+            pdf: 1.0,
+        })
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,113 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    aabb::Aabb,
+    lights::{Light, LightSample},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Disk of constant radiance at infinity like the sun seen from the ground, unlike the
+/// directional light it casts soft shadows. `direction` points towards the disk and
+/// `angular_radius` is given in degrees.
+pub(crate) struct SunLight {
+    direction: Vec3,
+    radiance: Vec3,
+    cos_max: f32,
+    center: Vec3,
+    radius: f32,
+}
+
+impl SunLight {
+    pub(crate) fn new(direction: Vec3, radiance: Vec3, angular_radius: f32) -> Self {
+        Self {
+            direction: direction.normalize(),
+            radiance,
+            cos_max: angular_radius.to_radians().cos(),
+            center: Vec3::new(0.0, 0.0, 0.0),
+            radius: 0.0,
+        }
+    }
+
+    fn solid_angle(&self) -> f32 {
+        2.0 * PI * (1.0 - self.cos_max)
+    }
+
+    /// Uniform direction inside the cone covered by the disk.
+    fn sample_direction(&self) -> Vec3 {
+        let mut rand = sampler::rng();
+        let cos_theta = 1.0 - rand.gen::<f32>() * (1.0 - self.cos_max);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand.gen::<f32>();
+        Onb::from_w(self.direction).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+
+    fn contains(&self, direction: Vec3) -> bool {
+        direction.normalize().dot(self.direction) >= self.cos_max
+    }
+}
+
+impl Light for SunLight {
+    fn emit(&self) -> Option<(Ray, Vec3)> {
+        if self.radius <= 0.0 {
+            return None;
+        }
+
+        // Photons start on a disk covering the bounding sphere of the scene
+        let direction = self.sample_direction();
+        let disk = Onb::from_w(direction).local(math::random_in_unit_disk());
+        let origin = self.center + (direction + disk) * self.radius;
+        let flux = self.radiance * (self.solid_angle() * PI * self.radius * self.radius);
+
+        let time = sampler::rng().gen::<f32>();
+        Some((Ray::new(origin, -direction, time), flux))
+    }
+
+    fn power(&self) -> f32 {
+        math::luminance(self.radiance) * self.solid_angle() * PI * self.radius * self.radius
+    }
+
+    fn sample_li(&self, _point: Vec3, _time: f32) -> Option<LightSample> {
+        Some(LightSample {
+            direction: self.sample_direction(),
+            distance: f32::INFINITY,
+            radiance: self.radiance,
+            pdf: 1.0 / self.solid_angle(),
+        })
+    }
+
+    fn pdf_li(&self, ray: &Ray, time_max: f32) -> f32 {
+        if time_max.is_finite() || !self.contains(ray.direction()) {
+            return 0.0;
+        }
+
+        1.0 / self.solid_angle()
+    }
+
+    fn environment(&self, direction: Vec3) -> Vec3 {
+        if self.contains(direction) {
+            self.radiance
+        } else {
+            Vec3::new(0.0, 0.0, 0.0)
+        }
+    }
+
+    fn preprocess(&mut self, bounds: &Aabb) {
+        self.center = (bounds.minimum() + bounds.maximum()) / 2.0;
+        self.radius = (bounds.maximum() - self.center).magnitude();
+    }
+}
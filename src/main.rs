@@ -8,14 +8,25 @@ mod aabb;
 mod application;
 mod arguments;
 mod camera;
+mod density_fields;
+mod distribution;
+mod film;
 mod hit_record;
 mod hittable;
+mod integrators;
+mod lights;
 mod logger;
 mod materials;
 mod math;
+mod media;
+mod microfacet;
+mod onb;
 mod perlin_noise;
 mod ray;
+mod sampler;
+mod spectrum;
 mod textures;
+mod world;
 
 use application::Application;
 use arguments::Arguments;
@@ -0,0 +1,94 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    ray::Ray,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+
+/// Perturbs the shading normal of the wrapped material as if the surface was displaced
+/// along its normal by the first channel of the height texture times `scale`.
+#[derive(Clone)]
+pub(crate) struct BumpMap<M: Material, T: Texture> {
+    material: M,
+    height: T,
+    scale: f32,
+}
+
+impl<M: Material, T: Texture> BumpMap<M, T> {
+    // Step in texture space of the finite differences
+    const DELTA: f32 = 0.0005;
+
+    pub(crate) fn new(material: M, height: T, scale: f32) -> Self {
+        Self {
+            material,
+            height,
+            scale,
+        }
+    }
+
+    fn shade<'a>(&self, ray: &Ray, hit_record: &HitRecord<'a>) -> HitRecord<'a> {
+        if math::near_zero(hit_record.tangent) || math::near_zero(hit_record.bitangent) {
+            return *hit_record;
+        }
+
+        let normal = if hit_record.front_face {
+            hit_record.normal
+        } else {
+            -hit_record.normal
+        };
+
+        let (u, v, point) = (hit_record.u, hit_record.v, hit_record.point);
+        let height = |u: f32, v: f32, point: Vec3| self.height.value(u, v, point).x * self.scale;
+        let displacement = height(u, v, point);
+        let displacement_u = height(u + Self::DELTA, v, point + Self::DELTA * hit_record.tangent);
+        let displacement_v = height(
+            u,
+            v + Self::DELTA,
+            point + Self::DELTA * hit_record.bitangent,
+        );
+
+        let tangent = hit_record.tangent + (displacement_u - displacement) / Self::DELTA * normal;
+        let bitangent =
+            hit_record.bitangent + (displacement_v - displacement) / Self::DELTA * normal;
+
+        let mut perturbed = tangent.cross(bitangent).normalize();
+        if perturbed.dot(normal) < 0.0 {
+            perturbed = -perturbed;
+        }
+
+        hit_record.with_shading_normal(ray, perturbed)
+    }
+}
+
+impl<M: Material, T: Texture> Material for BumpMap<M, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        self.material.scatter(ray, &self.shade(ray, hit_record))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        self.material
+            .evaluate(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        self.material
+            .pdf(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn samples_lights(&self) -> bool {
+        self.material.samples_lights()
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        self.material.emitted(ray, hit_record)
+    }
+}
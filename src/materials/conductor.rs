@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    microfacet::{Ggx, MicrofacetDistribution},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::{f32::consts::PI, sync::Arc};
+
+/// Rough metal following the GGX microfacet model. The roughness is read from the first
+/// channel of the texture and the complex index of refraction is given per color channel.
+#[derive(Clone)]
+pub(crate) struct Conductor<T: Texture> {
+    eta: Vec3,
+    k: Vec3,
+    roughness: T,
+    bitangent_roughness: Option<Arc<dyn Texture>>,
+    rotation: Option<Arc<dyn Texture>>,
+}
+
+impl<T: Texture> Conductor<T> {
+    pub(crate) fn new(eta: Vec3, k: Vec3, roughness: T) -> Self {
+        Self {
+            eta,
+            k,
+            roughness,
+            bitangent_roughness: None,
+            rotation: None,
+        }
+    }
+
+    /// Makes the surface anisotropic, the roughness passed to the constructor then only
+    /// applies along the tangent of the surface.
+    pub(crate) fn with_bitangent_roughness(self, roughness: impl Texture + 'static) -> Self {
+        Self {
+            bitangent_roughness: Some(Arc::new(roughness)),
+            ..self
+        }
+    }
+
+    /// Rotates the tangent around the normal by the first channel of the texture, where
+    /// one is a full turn.
+    pub(crate) fn with_rotation(self, rotation: impl Texture + 'static) -> Self {
+        Self {
+            rotation: Some(Arc::new(rotation)),
+            ..self
+        }
+    }
+
+    pub(crate) fn gold(roughness: T) -> Self {
+        Self::new(
+            Vec3::new(0.143, 0.374, 1.442),
+            Vec3::new(3.983, 2.385, 1.603),
+            roughness,
+        )
+    }
+
+    pub(crate) fn copper(roughness: T) -> Self {
+        Self::new(
+            Vec3::new(0.200, 0.924, 1.102),
+            Vec3::new(3.912, 2.452, 2.142),
+            roughness,
+        )
+    }
+
+    pub(crate) fn aluminium(roughness: T) -> Self {
+        Self::new(
+            Vec3::new(1.657, 0.880, 0.521),
+            Vec3::new(9.224, 6.270, 4.837),
+            roughness,
+        )
+    }
+
+    pub(crate) fn silver(roughness: T) -> Self {
+        Self::new(
+            Vec3::new(0.155, 0.117, 0.138),
+            Vec3::new(4.828, 3.122, 2.147),
+            roughness,
+        )
+    }
+
+    fn distribution(&self, hit_record: &HitRecord) -> Ggx {
+        let (u, v, point) = (hit_record.u, hit_record.v, hit_record.point);
+        let roughness = self.roughness.value(u, v, point).x.clamp(0.0, 1.0);
+        let Some(bitangent_roughness) = &self.bitangent_roughness else {
+            return Ggx::from_roughness(roughness);
+        };
+
+        let bitangent_roughness = bitangent_roughness.value(u, v, point).x.clamp(0.0, 1.0);
+        Ggx::new(
+            roughness * roughness,
+            bitangent_roughness * bitangent_roughness,
+        )
+    }
+
+    /// Shading frame with the x axis along the tangent, which only matters for anisotropic
+    /// surfaces.
+    fn frame(&self, hit_record: &HitRecord) -> Onb {
+        if self.bitangent_roughness.is_none() {
+            return Onb::from_w(hit_record.normal);
+        }
+
+        let onb = Onb::from_wu(hit_record.normal, hit_record.tangent);
+        let Some(rotation) = &self.rotation else {
+            return onb;
+        };
+
+        let angle = 2.0
+            * PI
+            * rotation
+                .value(hit_record.u, hit_record.v, hit_record.point)
+                .x;
+        Onb::from_wu(
+            hit_record.normal,
+            onb.local(Vec3::new(angle.cos(), angle.sin(), 0.0)),
+        )
+    }
+}
+
+impl<T: Texture> Material for Conductor<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb = self.frame(hit_record);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let distribution = self.distribution(hit_record);
+        if distribution.is_smooth() {
+            let direction = math::reflect(ray.direction().normalize(), hit_record.normal);
+            return Some(ScatterRecord::specular(
+                math::fresnel_conductor(outgoing.z, self.eta, self.k),
+                Ray::new(hit_record.point, direction, ray.time()),
+            ));
+        }
+
+        let mut rand = sampler::rng();
+        let normal = distribution.sample_normal(outgoing, rand.gen(), rand.gen());
+        let incoming = math::reflect(-outgoing, normal);
+        if incoming.z <= 0.0 {
+            return None;
+        }
+
+        // Sampling visible normals cancels everything but the Fresnel and shadowing terms
+        let fresnel = math::fresnel_conductor(outgoing.dot(normal), self.eta, self.k);
+        let shadowing =
+            distribution.masking_shadowing(outgoing, incoming) / distribution.masking(outgoing);
+
+        Some(ScatterRecord::new(
+            fresnel * shadowing,
+            Ray::new(hit_record.point, onb.local(incoming), ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let distribution = self.distribution(hit_record);
+        let onb = self.frame(hit_record);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if distribution.is_smooth() || outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let half = (outgoing + incoming).normalize();
+        let fresnel = math::fresnel_conductor(outgoing.dot(half), self.eta, self.k);
+        fresnel
+            * (distribution.distribution(half) * distribution.masking_shadowing(outgoing, incoming)
+                / (4.0 * outgoing.z))
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let distribution = self.distribution(hit_record);
+        let onb = self.frame(hit_record);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if distribution.is_smooth() || outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return 0.0;
+        }
+
+        // Reflecting about the half vector scales its density by the Jacobian of the mirror
+        let half = (outgoing + incoming).normalize();
+        distribution.pdf(outgoing, half) / (4.0 * outgoing.dot(half))
+    }
+}
@@ -6,33 +6,80 @@
 
 use crate::{
     hit_record::HitRecord,
-    materials::Material,
+    materials::{Material, ScatterRecord},
     math::{self, Vec3},
     ray::Ray,
+    sampler,
 };
 
 use cgmath::InnerSpace;
 use rand::Rng;
 
+#[derive(Clone, Copy, Debug)]
+enum RefractiveIndex {
+    Constant(f32),
+    Cauchy { a: f32, b: f32 },
+    Sellmeier { b: [f32; 3], c: [f32; 3] },
+}
+
+impl RefractiveIndex {
+    // Sodium D line, the wavelength catalog indices are usually given for
+    const REFERENCE_WAVELENGTH: f32 = 587.6;
+
+    fn at(&self, wavelength: f32) -> f32 {
+        // Both dispersion formulas take the wavelength in micrometers
+        let micrometers = wavelength / 1000.0;
+        let squared = micrometers * micrometers;
+
+        match *self {
+            Self::Constant(index) => index,
+            Self::Cauchy { a, b } => a + b / squared,
+            Self::Sellmeier { b, c } => (1.0
+                + (0..3)
+                    .map(|i| b[i] * squared / (squared - c[i]))
+                    .sum::<f32>())
+            .sqrt(),
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct Dielectric {
-    index_of_referaction: f32,
+    refractive_index: RefractiveIndex,
 }
 
 impl Dielectric {
-    pub(crate) fn new(index_of_referaction: f32) -> Self {
+    pub(crate) fn new(index_of_refraction: f32) -> Self {
+        Self {
+            refractive_index: RefractiveIndex::Constant(index_of_refraction),
+        }
+    }
+
+    /// Dispersive dielectric following Cauchy's equation, `b` is given in square micrometers.
+    pub(crate) fn cauchy(a: f32, b: f32) -> Self {
+        Self {
+            refractive_index: RefractiveIndex::Cauchy { a, b },
+        }
+    }
+
+    /// Dispersive dielectric following the Sellmeier equation, `c` is given in square
+    /// micrometers.
+    pub(crate) fn sellmeier(b: [f32; 3], c: [f32; 3]) -> Self {
         Self {
-            index_of_referaction,
+            refractive_index: RefractiveIndex::Sellmeier { b, c },
         }
     }
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Vec3, Ray)> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let index_of_refraction = self.refractive_index.at(ray
+            .wavelength()
+            .unwrap_or(RefractiveIndex::REFERENCE_WAVELENGTH));
         let refraction_ratio = if hit_record.front_face {
-            1.0 / self.index_of_referaction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_referaction
+            index_of_refraction
         };
 
         let unit_direction = ray.direction().normalize();
@@ -40,7 +87,7 @@ impl Material for Dielectric {
         let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
 
         let cannot_refract = (refraction_ratio * sin_theta) > 1.0;
-        let mut rand = rand::thread_rng();
+        let mut rand = sampler::rng();
         let direction =
             if cannot_refract || math::reflectance(cos_theta, refraction_ratio) > rand.gen() {
                 math::reflect(unit_direction, hit_record.normal)
@@ -48,13 +95,14 @@ impl Material for Dielectric {
                 math::refract(unit_direction, hit_record.normal, refraction_ratio)
             };
 
-        Some((
-            Vec3::new(1.0, 1.0, 1.0),
-            Ray::new(hit_record.point, direction, ray.time()),
-        ))
-    }
-
-    fn emitted(&self, _u: f32, _v: f32, _point: Vec3) -> Vec3 {
-        Vec3::new(0.0, 0.0, 0.0)
+        let attenuation = Vec3::new(1.0, 1.0, 1.0);
+        let scattered = Ray::new(hit_record.point, direction, ray.time());
+        let dispersive = !matches!(self.refractive_index, RefractiveIndex::Constant(_))
+            && ray.wavelength().is_some();
+        if dispersive {
+            Some(ScatterRecord::dispersive(attenuation, scattered))
+        } else {
+            Some(ScatterRecord::specular(attenuation, scattered))
+        }
     }
 }
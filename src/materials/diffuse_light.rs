@@ -4,25 +4,109 @@
  * SPDX-License-Identifier: MIT
  */
 
-use crate::{hit_record::HitRecord, materials::Material, math::Vec3, ray::Ray, textures::Texture};
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::Vec3,
+    ray::Ray,
+    textures::Texture,
+};
 
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use cgmath::InnerSpace;
+
+#[derive(Clone, Copy, Debug)]
+enum Profile {
+    OneSided,
+    TwoSided,
+    CosinePower(f32),
+}
+
+/// Emits light from the front face of a surface, or from both faces if it's two sided.
 #[derive(Clone)]
 pub(crate) struct DiffuseLight<T: Texture> {
     emit: T,
+    intensity: f32,
+    profile: Profile,
+    // Shared between clones, so the shape in the scene learns the index of its light
+    light: Arc<AtomicUsize>,
 }
 
 impl<T: Texture> DiffuseLight<T> {
+    const NO_LIGHT: usize = usize::MAX;
+    const SHARED_LIGHT: usize = usize::MAX - 1;
+
     pub(crate) fn new(emit: T) -> Self {
-        Self { emit }
+        Self {
+            emit,
+            intensity: 1.0,
+            profile: Profile::OneSided,
+            light: Arc::new(AtomicUsize::new(Self::NO_LIGHT)),
+        }
+    }
+
+    /// Scales the emitted texture, which lets textures in the unit range light a scene.
+    pub(crate) fn with_intensity(self, intensity: f32) -> Self {
+        Self { intensity, ..self }
+    }
+
+    pub(crate) fn two_sided(self) -> Self {
+        Self {
+            profile: Profile::TwoSided,
+            ..self
+        }
+    }
+
+    /// Focuses the emission around the normal like a spot light, the radiance falls off
+    /// with the cosine to the normal raised to `exponent`.
+    pub(crate) fn with_cosine_power(self, exponent: f32) -> Self {
+        Self {
+            profile: Profile::CosinePower(exponent),
+            ..self
+        }
     }
 }
 
 impl<T: Texture> Material for DiffuseLight<T> {
-    fn scatter(&self, _ray: &Ray, _hit_record: &HitRecord) -> Option<(Vec3, Ray)> {
+    fn scatter(&self, _ray: &Ray, _hit_record: &HitRecord) -> Option<ScatterRecord> {
         None
     }
 
-    fn emitted(&self, u: f32, v: f32, point: Vec3) -> Vec3 {
-        self.emit.value(u, v, point)
+    fn set_light(&self, index: usize) {
+        if let Err(current) =
+            self.light
+                .compare_exchange(Self::NO_LIGHT, index, Ordering::Relaxed, Ordering::Relaxed)
+        {
+            if current != index {
+                self.light.store(Self::SHARED_LIGHT, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn light(&self) -> Option<usize> {
+        let index = self.light.load(Ordering::Relaxed);
+        (index != Self::NO_LIGHT && index != Self::SHARED_LIGHT).then_some(index)
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        if !hit_record.front_face && !matches!(self.profile, Profile::TwoSided) {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let emit = self
+            .emit
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            * self.intensity;
+        match self.profile {
+            Profile::OneSided | Profile::TwoSided => emit,
+            Profile::CosinePower(exponent) => {
+                let cosine = hit_record.normal.dot(-ray.direction().normalize());
+                emit * cosine.max(0.0).powf(exponent)
+            }
+        }
     }
 }
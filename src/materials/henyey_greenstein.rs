@@ -0,0 +1,110 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::Vec3,
+    onb::Onb,
+    ray::Ray,
+    sampler,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::f32::consts::PI;
+
+/// Henyey-Greenstein phase function, positive asymmetry scatters forward and negative
+/// asymmetry backward. The double lobe variant blends a forward and a backward lobe.
+#[derive(Clone)]
+pub(crate) struct HenyeyGreenstein<T: Texture> {
+    albedo: T,
+    forward_asymmetry: f32,
+    backward_asymmetry: f32,
+    forward_weight: f32,
+}
+
+impl<T: Texture> HenyeyGreenstein<T> {
+    pub(crate) fn new(albedo: T, asymmetry: f32) -> Self {
+        Self::double_lobe(albedo, asymmetry, asymmetry, 1.0)
+    }
+
+    pub(crate) fn double_lobe(
+        albedo: T,
+        forward_asymmetry: f32,
+        backward_asymmetry: f32,
+        forward_weight: f32,
+    ) -> Self {
+        Self {
+            albedo,
+            forward_asymmetry: forward_asymmetry.clamp(-0.999, 0.999),
+            backward_asymmetry: backward_asymmetry.clamp(-0.999, 0.999),
+            forward_weight: forward_weight.clamp(0.0, 1.0),
+        }
+    }
+
+    fn lobe(asymmetry: f32, cos_theta: f32) -> f32 {
+        let denominator = 1.0 + asymmetry * asymmetry - 2.0 * asymmetry * cos_theta;
+        (1.0 - asymmetry * asymmetry) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    fn sample_lobe(asymmetry: f32) -> f32 {
+        let mut rand = sampler::rng();
+        let sample = rand.gen::<f32>();
+        if asymmetry.abs() < 1e-3 {
+            return 1.0 - 2.0 * sample;
+        }
+
+        let square = (1.0 - asymmetry * asymmetry) / (1.0 - asymmetry + 2.0 * asymmetry * sample);
+        ((1.0 + asymmetry * asymmetry - square * square) / (2.0 * asymmetry)).clamp(-1.0, 1.0)
+    }
+
+    /// Probability density of scattering by `cos_theta` relative to the travel direction,
+    /// which the importance sampling in `scatter` follows exactly.
+    fn phase(&self, cos_theta: f32) -> f32 {
+        self.forward_weight * Self::lobe(self.forward_asymmetry, cos_theta)
+            + (1.0 - self.forward_weight) * Self::lobe(self.backward_asymmetry, cos_theta)
+    }
+}
+
+impl<T: Texture> Material for HenyeyGreenstein<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let mut rand = sampler::rng();
+        let asymmetry = if rand.gen::<f32>() < self.forward_weight {
+            self.forward_asymmetry
+        } else {
+            self.backward_asymmetry
+        };
+
+        let cos_theta = Self::sample_lobe(asymmetry);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * rand.gen::<f32>();
+        let direction = Onb::from_w(ray.direction()).local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ));
+
+        // Sampling proportional to the phase function leaves only the albedo as weight
+        Some(ScatterRecord::new(
+            self.albedo
+                .value(hit_record.u, hit_record.v, hit_record.point),
+            Ray::new(hit_record.point, direction, ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let cos_theta = ray.direction().normalize().dot(direction.normalize());
+        self.albedo
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            * self.phase(cos_theta)
+    }
+
+    fn pdf(&self, ray: &Ray, _hit_record: &HitRecord, direction: Vec3) -> f32 {
+        self.phase(ray.direction().normalize().dot(direction.normalize()))
+    }
+}
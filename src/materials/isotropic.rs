@@ -6,12 +6,14 @@
 
 use crate::{
     hit_record::HitRecord,
-    materials::Material,
+    materials::{Material, ScatterRecord},
     math::{self, Vec3},
     ray::Ray,
     textures::Texture,
 };
 
+use std::f32::consts::PI;
+
 #[derive(Clone)]
 pub(crate) struct Isotropic<T: Texture> {
     albedo: T,
@@ -24,15 +26,21 @@ impl<T: Texture> Isotropic<T> {
 }
 
 impl<T: Texture> Material for Isotropic<T> {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Vec3, Ray)> {
-        Some((
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::new(
             self.albedo
                 .value(hit_record.u, hit_record.v, hit_record.point),
             Ray::new(hit_record.point, math::random_in_unit_sphere(), ray.time()),
         ))
     }
 
-    fn emitted(&self, _u: f32, _v: f32, _point: Vec3) -> Vec3 {
-        Vec3::new(0.0, 0.0, 0.0)
+    fn evaluate(&self, _ray: &Ray, hit_record: &HitRecord, _direction: Vec3) -> Vec3 {
+        self.albedo
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            / (4.0 * PI)
+    }
+
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> f32 {
+        1.0 / (4.0 * PI)
     }
 }
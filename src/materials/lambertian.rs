@@ -6,12 +6,15 @@
 
 use crate::{
     hit_record::HitRecord,
-    materials::Material,
+    materials::{Material, ScatterRecord},
     math::{self, Vec3},
     ray::Ray,
     textures::Texture,
 };
 
+use cgmath::InnerSpace;
+use std::f32::consts::PI;
+
 #[derive(Clone)]
 pub(crate) struct Lambertian<T: Texture> {
     albedo: T,
@@ -24,20 +27,31 @@ impl<T: Texture> Lambertian<T> {
 }
 
 impl<T: Texture> Material for Lambertian<T> {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Vec3, Ray)> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         let mut scatter_direction = hit_record.normal + math::random_unit_vector();
         if math::near_zero(scatter_direction) {
             scatter_direction = hit_record.normal;
         }
 
-        Some((
+        Some(ScatterRecord::new(
             self.albedo
                 .value(hit_record.u, hit_record.v, hit_record.point),
             Ray::new(hit_record.point, scatter_direction, ray.time()),
         ))
     }
 
-    fn emitted(&self, _u: f32, _v: f32, _point: Vec3) -> Vec3 {
-        Vec3::new(0.0, 0.0, 0.0)
+    fn evaluate(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let cosine = hit_record.normal.dot(direction.normalize());
+        if cosine <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        self.albedo
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            * (cosine / PI)
+    }
+
+    fn pdf(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        hit_record.normal.dot(direction.normalize()).max(0.0) / PI
     }
 }
@@ -0,0 +1,232 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    microfacet::{Ggx, MicrofacetDistribution},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+    textures::Texture,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use rand::Rng;
+
+/// Dielectric coat over an arbitrary base material, optionally absorbing light in between.
+/// Sampling walks the stack of layers stochastically, evaluation uses the single
+/// scattering approximation by Weidlich and Wilkie. Integrators rely on the walk alone.
+#[derive(Clone)]
+pub(crate) struct Layered<M: Material, T: Texture> {
+    base: M,
+    index_of_refraction: f32,
+    roughness: T,
+    absorption: Vec3,
+}
+
+impl<M: Material, T: Texture> Layered<M, T> {
+    const MAX_BOUNCES: u32 = 16;
+
+    pub(crate) fn new(base: M, index_of_refraction: f32, roughness: T) -> Self {
+        Self {
+            base,
+            index_of_refraction,
+            roughness,
+            absorption: Vec3::new(0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Tints the layer between coat and base, `absorption` is given per unit of thickness.
+    pub(crate) fn with_absorption(self, absorption: Vec3, thickness: f32) -> Self {
+        Self {
+            absorption: absorption * thickness,
+            ..self
+        }
+    }
+
+    fn distribution(&self, hit_record: &HitRecord) -> Ggx {
+        let roughness = self
+            .roughness
+            .value(hit_record.u, hit_record.v, hit_record.point);
+        Ggx::from_roughness(roughness.x.clamp(0.0, 1.0))
+    }
+
+    /// Transmittance of crossing the layer once in a direction with the given cosine.
+    fn transmittance(&self, cosine: f32) -> Vec3 {
+        let depth = 1.0 / cosine.abs().max(1e-4);
+        Vec3::new(
+            (-self.absorption.x * depth).exp(),
+            (-self.absorption.y * depth).exp(),
+            (-self.absorption.z * depth).exp(),
+        )
+    }
+
+    /// Scatters at the coat, in a frame where the z axis points to the side of `outgoing`.
+    /// Returns the new direction and its weight.
+    fn scatter_coat(distribution: &Ggx, outgoing: Vec3, eta: f32) -> Option<(Vec3, f32)> {
+        let mut rand = sampler::rng();
+        let normal = if distribution.is_smooth() {
+            Vec3::new(0.0, 0.0, 1.0)
+        } else {
+            distribution.sample_normal(outgoing, rand.gen(), rand.gen())
+        };
+
+        let cosine = outgoing.dot(normal);
+        if cosine <= 0.0 {
+            return None;
+        }
+
+        let incoming = if rand.gen::<f32>() < math::fresnel_dielectric(cosine, eta) {
+            math::reflect(-outgoing, normal)
+        } else {
+            let incoming = math::refract(-outgoing, normal, 1.0 / eta).normalize();
+            if incoming.z >= 0.0 {
+                return None;
+            }
+
+            incoming
+        };
+
+        let weight = if distribution.is_smooth() {
+            1.0
+        } else {
+            distribution.masking_shadowing(outgoing, incoming) / distribution.masking(outgoing)
+        };
+
+        Some((incoming, weight))
+    }
+
+    fn flip(direction: Vec3) -> Vec3 {
+        Vec3::new(direction.x, direction.y, -direction.z)
+    }
+
+    /// Direction inside the layer of light refracted at a flat coat, `None` on total
+    /// internal reflection.
+    fn refract_flat(&self, direction: Vec3) -> Option<Vec3> {
+        let cosine = direction.z;
+        let sin_squared =
+            (1.0 - cosine * cosine) / (self.index_of_refraction * self.index_of_refraction);
+        if sin_squared >= 1.0 {
+            return None;
+        }
+
+        Some(math::refract(
+            -direction,
+            Vec3::new(0.0, 0.0, 1.0),
+            1.0 / self.index_of_refraction,
+        ))
+    }
+}
+
+impl<M: Material, T: Texture> Material for Layered<M, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let distribution = self.distribution(hit_record);
+        let (mut direction, coat_weight) =
+            Self::scatter_coat(&distribution, outgoing, self.index_of_refraction)?;
+        let mut weight = Vec3::new(coat_weight, coat_weight, coat_weight);
+        let mut specular = distribution.is_smooth();
+
+        // Light reflected by the coat never enters the layer
+        let mut bounces = 0;
+        while direction.z < 0.0 {
+            if bounces == Self::MAX_BOUNCES {
+                return None;
+            }
+
+            bounces += 1;
+
+            weight.mul_assign_element_wise(self.transmittance(direction.z));
+            let base_ray = Ray::new(hit_record.point, onb.local(direction), ray.time());
+            let base_record = self.base.scatter(&base_ray, hit_record)?;
+            weight.mul_assign_element_wise(base_record.attenuation);
+            specular &= base_record.specular;
+
+            direction = onb.world_to_local(base_record.ray.direction().normalize());
+            if direction.z <= 0.0 {
+                return None;
+            }
+
+            weight.mul_assign_element_wise(self.transmittance(direction.z));
+
+            // Seen from inside the layer the coat faces downwards
+            let (incoming, coat_weight) = Self::scatter_coat(
+                &distribution,
+                Self::flip(-direction),
+                1.0 / self.index_of_refraction,
+            )?;
+            direction = Self::flip(incoming);
+            weight *= coat_weight;
+        }
+
+        let scattered = Ray::new(hit_record.point, onb.local(direction), ray.time());
+        if specular {
+            Some(ScatterRecord::specular(weight, scattered))
+        } else {
+            Some(ScatterRecord::new(weight, scattered))
+        }
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let eta = self.index_of_refraction;
+        let distribution = self.distribution(hit_record);
+        let half = (outgoing + incoming).normalize();
+
+        let coat = if distribution.is_smooth() {
+            0.0
+        } else {
+            math::fresnel_dielectric(outgoing.dot(half), eta)
+                * distribution.distribution(half)
+                * distribution.masking_shadowing(outgoing, incoming)
+                / (4.0 * outgoing.z)
+        };
+
+        // Light refracted into the layer, scattered once by the base and refracted out
+        let (Some(inner_outgoing), Some(inner_incoming)) =
+            (self.refract_flat(outgoing), self.refract_flat(incoming))
+        else {
+            return Vec3::new(coat, coat, coat);
+        };
+
+        let base_ray = Ray::new(hit_record.point, onb.local(inner_outgoing), ray.time());
+        let base = self
+            .base
+            .evaluate(&base_ray, hit_record, -onb.local(inner_incoming));
+        let transmission = (1.0 - math::fresnel_dielectric(outgoing.z, eta))
+            * (1.0 - math::fresnel_dielectric(incoming.z, eta))
+            / (eta * eta);
+
+        Vec3::new(coat, coat, coat)
+            + base
+                .mul_element_wise(self.transmittance(inner_outgoing.z))
+                .mul_element_wise(self.transmittance(inner_incoming.z))
+                * transmission
+    }
+
+    /// `evaluate` only covers single scattering while the random walk scatters any number
+    /// of times, weighting light samples with it would blend two different functions.
+    fn samples_lights(&self) -> bool {
+        false
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        self.base.emitted(ray, hit_record)
+    }
+}
@@ -0,0 +1,299 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    distribution::Distribution2D,
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    fs,
+    io::{self, ErrorKind},
+    sync::Arc,
+};
+
+const THETA_HALF_RESOLUTION: usize = 90;
+const THETA_DIFFERENCE_RESOLUTION: usize = 90;
+const PHI_DIFFERENCE_RESOLUTION: usize = 180;
+const ENTRY_COUNT: usize =
+    THETA_HALF_RESOLUTION * THETA_DIFFERENCE_RESOLUTION * PHI_DIFFERENCE_RESOLUTION;
+
+// Scale of the stored values per color channel
+const SCALE: [f32; 3] = [1.0 / 1500.0, 1.15 / 1500.0, 1.66 / 1500.0];
+
+// Resolution of the sampling tables, the outgoing elevation picks the table and the incoming
+// direction is tabulated by its elevation and its azimuth relative to the outgoing one
+const OUTGOING_RESOLUTION: usize = 16;
+const THETA_RESOLUTION: usize = 32;
+const PHI_RESOLUTION: usize = 32;
+
+// Share of the samples drawn from the cosine distribution, which covers directions the
+// coarse tables miss
+const COSINE_PROBABILITY: f32 = 0.1;
+
+/// Isotropic BRDF measured by Matusik et al. loaded from the MERL binary format. Directions
+/// are importance sampled from tables of the reflectance over the incoming hemisphere.
+#[derive(Clone)]
+pub(crate) struct Measured {
+    values: Arc<[f32]>,
+    tables: Arc<[Distribution2D]>,
+}
+
+impl Measured {
+    pub(crate) fn load(path: &str) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        let invalid = |message: &str| io::Error::new(ErrorKind::InvalidData, message.to_owned());
+
+        let header = bytes
+            .get(..12)
+            .ok_or_else(|| invalid("missing header of the measured BRDF"))?;
+        let entry_count = header
+            .chunks_exact(4)
+            .map(|chunk| usize::try_from(i32::from_le_bytes(chunk.try_into().unwrap())).ok())
+            .try_fold(1usize, |count, dimension| count.checked_mul(dimension?));
+        if entry_count != Some(ENTRY_COUNT) {
+            return Err(invalid("unexpected dimensions of the measured BRDF"));
+        }
+
+        let data = &bytes[12..];
+        if data.len() != 3 * ENTRY_COUNT * 8 {
+            return Err(invalid("unexpected size of the measured BRDF"));
+        }
+
+        let values: Vec<f32> = data
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()) as f32)
+            .collect();
+
+        let mut measured = Self {
+            values: values.into(),
+            tables: Vec::new().into(),
+        };
+        measured.tables = measured.build_tables().into();
+
+        Ok(measured)
+    }
+
+    fn build_tables(&self) -> Vec<Distribution2D> {
+        (0..OUTGOING_RESOLUTION)
+            .map(|outgoing_index| {
+                let theta = (outgoing_index as f32 + 0.5) / OUTGOING_RESOLUTION as f32 * FRAC_PI_2;
+                let outgoing = Vec3::new(theta.sin(), 0.0, theta.cos());
+
+                let mut values = Vec::with_capacity(THETA_RESOLUTION * PHI_RESOLUTION);
+                for theta_index in 0..THETA_RESOLUTION {
+                    for phi_index in 0..PHI_RESOLUTION {
+                        let incoming = Self::table_direction(
+                            (phi_index as f32 + 0.5) / PHI_RESOLUTION as f32,
+                            (theta_index as f32 + 0.5) / THETA_RESOLUTION as f32,
+                        );
+
+                        // Weighted by the area of the cell on the sphere
+                        let reflectance = math::luminance(self.lookup(outgoing, incoming));
+                        let sin_theta = (1.0 - incoming.z * incoming.z).max(0.0).sqrt();
+                        values.push(reflectance * incoming.z * sin_theta);
+                    }
+                }
+
+                Distribution2D::new(&values, PHI_RESOLUTION, THETA_RESOLUTION)
+            })
+            .collect()
+    }
+
+    /// Direction of a table position in the frame where the outgoing azimuth is zero.
+    fn table_direction(x: f32, y: f32) -> Vec3 {
+        let theta = y * FRAC_PI_2;
+        let phi = x * PI;
+        Vec3::new(
+            theta.sin() * phi.cos(),
+            theta.sin() * phi.sin(),
+            theta.cos(),
+        )
+    }
+
+    fn table(&self, outgoing: Vec3) -> &Distribution2D {
+        let theta = outgoing.z.clamp(0.0, 1.0).acos();
+        let index = (theta / FRAC_PI_2 * OUTGOING_RESOLUTION as f32) as usize;
+        &self.tables[index.min(OUTGOING_RESOLUTION - 1)]
+    }
+
+    /// Looks up the BRDF for two directions in the local shading frame, following the
+    /// half and difference angle parameterization of the reference implementation.
+    fn lookup(&self, outgoing: Vec3, incoming: Vec3) -> Vec3 {
+        let half = (outgoing + incoming).normalize();
+        let theta_half = half.z.clamp(-1.0, 1.0).acos();
+        let phi_half = half.y.atan2(half.x);
+
+        // Rotate the incoming direction into the frame of the half vector
+        let rotate = |vector: Vec3, axis_z: bool, angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            if axis_z {
+                Vec3::new(
+                    vector.x * cos - vector.y * sin,
+                    vector.x * sin + vector.y * cos,
+                    vector.z,
+                )
+            } else {
+                Vec3::new(
+                    vector.x * cos + vector.z * sin,
+                    vector.y,
+                    -vector.x * sin + vector.z * cos,
+                )
+            }
+        };
+        let difference = rotate(rotate(incoming, true, -phi_half), false, -theta_half);
+        let theta_difference = difference.z.clamp(-1.0, 1.0).acos();
+        let mut phi_difference = difference.y.atan2(difference.x);
+
+        // The BRDF is symmetric under reciprocity, only half of the azimuths are stored
+        if phi_difference < 0.0 {
+            phi_difference += PI;
+        }
+
+        let theta_half_index = if theta_half <= 0.0 {
+            0
+        } else {
+            ((theta_half / FRAC_PI_2).sqrt() * THETA_HALF_RESOLUTION as f32) as usize
+        }
+        .min(THETA_HALF_RESOLUTION - 1);
+        let theta_difference_index = ((theta_difference / FRAC_PI_2
+            * THETA_DIFFERENCE_RESOLUTION as f32) as usize)
+            .min(THETA_DIFFERENCE_RESOLUTION - 1);
+        let phi_difference_index = ((phi_difference / PI * PHI_DIFFERENCE_RESOLUTION as f32)
+            as usize)
+            .min(PHI_DIFFERENCE_RESOLUTION - 1);
+
+        let index = phi_difference_index
+            + theta_difference_index * PHI_DIFFERENCE_RESOLUTION
+            + theta_half_index * PHI_DIFFERENCE_RESOLUTION * THETA_DIFFERENCE_RESOLUTION;
+
+        // Negative entries mark directions without measurements
+        Vec3::new(
+            self.values[index].max(0.0) * SCALE[0],
+            self.values[index + ENTRY_COUNT].max(0.0) * SCALE[1],
+            self.values[index + 2 * ENTRY_COUNT].max(0.0) * SCALE[2],
+        )
+    }
+
+    fn local_pdf(&self, outgoing: Vec3, incoming: Vec3) -> f32 {
+        if incoming.z <= 0.0 {
+            return 0.0;
+        }
+
+        let theta = incoming.z.min(1.0).acos();
+        let sin_theta = theta.sin().max(1e-4);
+        let phi = (incoming.y.atan2(incoming.x) - outgoing.y.atan2(outgoing.x)).abs();
+        let phi = if phi > PI { 2.0 * PI - phi } else { phi };
+
+        // The table covers half of the azimuths and mirrors its samples to the other half
+        let table_pdf = self.table(outgoing).pdf(phi / PI, theta / FRAC_PI_2)
+            / (2.0 * PI * FRAC_PI_2 * sin_theta);
+        let cosine_pdf = incoming.z / PI;
+
+        (1.0 - COSINE_PROBABILITY) * table_pdf + COSINE_PROBABILITY * cosine_pdf
+    }
+}
+
+impl Material for Measured {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let mut rand = sampler::rng();
+        let incoming = if rand.gen::<f32>() < COSINE_PROBABILITY {
+            (Vec3::new(0.0, 0.0, 1.0) + math::random_unit_vector()).normalize()
+        } else {
+            let ((x, y), _) = self.table(outgoing).sample(rand.gen(), rand.gen());
+            let direction = Self::table_direction(x, y);
+            let sign = if rand.gen::<bool>() { 1.0 } else { -1.0 };
+            let phi = outgoing.y.atan2(outgoing.x) + sign * x * PI;
+            let sin_theta = (1.0 - direction.z * direction.z).max(0.0).sqrt();
+            Vec3::new(sin_theta * phi.cos(), sin_theta * phi.sin(), direction.z)
+        };
+
+        let pdf = self.local_pdf(outgoing, incoming);
+        if pdf <= 0.0 {
+            return None;
+        }
+
+        let attenuation = self.lookup(outgoing, incoming) * (incoming.z / pdf);
+        Some(ScatterRecord::new(
+            attenuation,
+            Ray::new(hit_record.point, onb.local(incoming), ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if outgoing.z <= 0.0 || incoming.z <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        self.lookup(outgoing, incoming) * incoming.z
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return 0.0;
+        }
+
+        self.local_pdf(outgoing, onb.world_to_local(direction.normalize()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(name: &str, dimensions: [i32; 3], value_count: usize) -> io::Result<Measured> {
+        let mut bytes: Vec<u8> = dimensions
+            .iter()
+            .flat_map(|dimension| dimension.to_le_bytes())
+            .collect();
+        bytes.resize(bytes.len() + value_count * 8, 0);
+
+        let path = std::env::temp_dir().join(format!("hyper-ray-tracer-{name}.binary"));
+        fs::write(&path, bytes)?;
+        let measured = Measured::load(path.to_str().unwrap());
+        fs::remove_file(&path)?;
+        measured
+    }
+
+    fn assert_invalid(result: io::Result<Measured>) {
+        match result {
+            Ok(_) => panic!("invalid measured BRDF was loaded"),
+            Err(error) => assert_eq!(error.kind(), ErrorKind::InvalidData),
+        }
+    }
+
+    #[test]
+    fn rejects_truncated_files() {
+        let dimensions = [90, 90, 180];
+        assert_invalid(load("truncated", dimensions, 3 * ENTRY_COUNT - 1));
+        assert_invalid(load("header-only", dimensions, 0));
+    }
+
+    #[test]
+    fn rejects_negative_dimensions() {
+        // The product of the dimensions still matches the expected entry count
+        assert_invalid(load("negative", [-90, -90, 180], 3 * ENTRY_COUNT));
+    }
+}
@@ -6,7 +6,7 @@
 
 use crate::{
     hit_record::HitRecord,
-    materials::Material,
+    materials::{Material, ScatterRecord},
     math::{self, Vec3},
     ray::Ray,
 };
@@ -26,7 +26,7 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Vec3, Ray)> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
         let reflected = math::reflect(ray.direction().normalize(), hit_record.normal);
         let scattered = Ray::new(
             hit_record.point,
@@ -35,13 +35,9 @@ impl Material for Metal {
         );
 
         if scattered.direction().dot(hit_record.normal) > 0.0 {
-            Some((self.albedo, scattered))
+            Some(ScatterRecord::specular(self.albedo, scattered))
         } else {
             None
         }
     }
-
-    fn emitted(&self, _u: f32, _v: f32, _point: Vec3) -> Vec3 {
-        Vec3::new(0.0, 0.0, 0.0)
-    }
 }
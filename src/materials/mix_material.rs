@@ -0,0 +1,79 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::Vec3,
+    ray::Ray,
+    sampler,
+    textures::Texture,
+};
+
+use rand::Rng;
+
+/// Blends two materials by the first channel of the mask, zero picks the first material
+/// and one the second. Each hit samples one of them with the probability of its share.
+#[derive(Clone)]
+pub(crate) struct MixMaterial<A: Material, B: Material, T: Texture> {
+    first: A,
+    second: B,
+    mask: T,
+}
+
+impl<A: Material, B: Material, T: Texture> MixMaterial<A, B, T> {
+    pub(crate) fn new(first: A, second: B, mask: T) -> Self {
+        Self {
+            first,
+            second,
+            mask,
+        }
+    }
+
+    /// Probability of sampling the second material, which is also its share of the blend.
+    fn probability(&self, hit_record: &HitRecord) -> f32 {
+        self.mask
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            .x
+            .clamp(0.0, 1.0)
+    }
+}
+
+impl<A: Material, B: Material, T: Texture> Material for MixMaterial<A, B, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let probability = self.probability(hit_record);
+
+        // The share of each material cancels with the probability of picking it
+        let mut rand = sampler::rng();
+        if rand.gen::<f32>() < probability {
+            self.second.scatter(ray, hit_record)
+        } else {
+            self.first.scatter(ray, hit_record)
+        }
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let probability = self.probability(hit_record);
+        self.first.evaluate(ray, hit_record, direction) * (1.0 - probability)
+            + self.second.evaluate(ray, hit_record, direction) * probability
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let probability = self.probability(hit_record);
+        self.first.pdf(ray, hit_record, direction) * (1.0 - probability)
+            + self.second.pdf(ray, hit_record, direction) * probability
+    }
+
+    fn samples_lights(&self) -> bool {
+        self.first.samples_lights() && self.second.samples_lights()
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        let probability = self.probability(hit_record);
+        self.first.emitted(ray, hit_record) * (1.0 - probability)
+            + self.second.emitted(ray, hit_record) * probability
+    }
+}
@@ -6,14 +6,100 @@
 
 use crate::{hit_record::HitRecord, math::Vec3, ray::Ray};
 
+pub(crate) mod bump_map;
+pub(crate) mod conductor;
 pub(crate) mod dielectric;
 pub(crate) mod diffuse_light;
+pub(crate) mod henyey_greenstein;
 pub(crate) mod isotropic;
 pub(crate) mod lambertian;
+pub(crate) mod layered;
+pub(crate) mod measured;
 pub(crate) mod metal;
+pub(crate) mod mix_material;
+pub(crate) mod normal_map;
+pub(crate) mod oren_nayar;
+pub(crate) mod pass_through;
+pub(crate) mod principled;
+pub(crate) mod rough_dielectric;
+pub(crate) mod thin_film;
+
+pub(crate) struct ScatterRecord {
+    pub(crate) attenuation: Vec3,
+    pub(crate) ray: Ray,
+    pub(crate) specular: bool,
+    pub(crate) dispersive: bool,
+}
+
+impl ScatterRecord {
+    pub(crate) fn new(attenuation: Vec3, ray: Ray) -> Self {
+        Self {
+            attenuation,
+            ray,
+            specular: false,
+            dispersive: false,
+        }
+    }
+
+    pub(crate) fn specular(attenuation: Vec3, ray: Ray) -> Self {
+        Self {
+            attenuation,
+            ray,
+            specular: true,
+            dispersive: false,
+        }
+    }
+
+    /// Specular scattering which depends on the wavelength of the ray.
+    pub(crate) fn dispersive(attenuation: Vec3, ray: Ray) -> Self {
+        Self {
+            attenuation,
+            ray,
+            specular: true,
+            dispersive: true,
+        }
+    }
+}
 
 pub(crate) trait Material: Send + Sync {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<(Vec3, Ray)>;
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord>;
+
+    /// Evaluates the scattering function times the cosine of the outgoing direction.
+    /// Specular materials can't be evaluated for arbitrary directions and return black.
+    fn evaluate(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
+
+    /// Density over solid angle of `scatter` choosing `direction`, used to weight light
+    /// samples against scattered rays. Specular materials never get light samples and
+    /// keep the default.
+    fn pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _direction: Vec3) -> f32 {
+        0.0
+    }
+
+    /// Whether `evaluate` and `pdf` match the distribution `scatter` samples. Materials
+    /// which only approximate it get no light samples and find lights by scattering alone,
+    /// the photon mapper walks through them instead of gathering.
+    fn samples_lights(&self) -> bool {
+        true
+    }
+
+    /// Remembers the index of the light in the world which emits from the surface.
+    fn set_light(&self, _index: usize) {}
+
+    /// Index of the only light emitting from surfaces with this material, `None` if it's
+    /// unknown or shared by several lights.
+    fn light(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the surface only separates two media, shadow rays pass through it.
+    fn is_interface(&self) -> bool {
+        false
+    }
 
-    fn emitted(&self, u: f32, v: f32, point: Vec3) -> Vec3;
+    /// Radiance emitted towards the origin of the ray which hit the surface.
+    fn emitted(&self, _ray: &Ray, _hit_record: &HitRecord) -> Vec3 {
+        Vec3::new(0.0, 0.0, 0.0)
+    }
 }
@@ -0,0 +1,82 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    ray::Ray,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+
+/// Perturbs the shading normal of the wrapped material with a tangent space normal map,
+/// usually an `ImageTexture` with the normal encoded in the usual color range.
+#[derive(Clone)]
+pub(crate) struct NormalMap<M: Material, T: Texture> {
+    material: M,
+    normal_map: T,
+}
+
+impl<M: Material, T: Texture> NormalMap<M, T> {
+    pub(crate) fn new(material: M, normal_map: T) -> Self {
+        Self {
+            material,
+            normal_map,
+        }
+    }
+
+    fn shade<'a>(&self, ray: &Ray, hit_record: &HitRecord<'a>) -> HitRecord<'a> {
+        if math::near_zero(hit_record.tangent) {
+            return *hit_record;
+        }
+
+        let normal = if hit_record.front_face {
+            hit_record.normal
+        } else {
+            -hit_record.normal
+        };
+
+        // Orthonormalize the tangent frame while keeping the handedness of the surface
+        let tangent = (hit_record.tangent - normal * normal.dot(hit_record.tangent)).normalize();
+        let mut bitangent = normal.cross(tangent);
+        if bitangent.dot(hit_record.bitangent) < 0.0 {
+            bitangent = -bitangent;
+        }
+
+        let color = self
+            .normal_map
+            .value(hit_record.u, hit_record.v, hit_record.point);
+        let local = 2.0 * color - Vec3::new(1.0, 1.0, 1.0);
+        let perturbed = (local.x * tangent + local.y * bitangent + local.z * normal).normalize();
+        hit_record.with_shading_normal(ray, perturbed)
+    }
+}
+
+impl<M: Material, T: Texture> Material for NormalMap<M, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        self.material.scatter(ray, &self.shade(ray, hit_record))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        self.material
+            .evaluate(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        self.material
+            .pdf(ray, &self.shade(ray, hit_record), direction)
+    }
+
+    fn samples_lights(&self) -> bool {
+        self.material.samples_lights()
+    }
+
+    fn emitted(&self, ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        self.material.emitted(ray, hit_record)
+    }
+}
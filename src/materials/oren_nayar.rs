@@ -0,0 +1,98 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    onb::Onb,
+    ray::Ray,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+use std::f32::consts::PI;
+
+/// Rough diffuse surface following the qualitative Oren-Nayar model, `sigma` is the
+/// standard deviation of the microfacet angles in degrees.
+#[derive(Clone)]
+pub(crate) struct OrenNayar<T: Texture> {
+    albedo: T,
+    a: f32,
+    b: f32,
+}
+
+impl<T: Texture> OrenNayar<T> {
+    pub(crate) fn new(albedo: T, sigma: f32) -> Self {
+        let sigma = sigma.to_radians();
+        let sigma_squared = sigma * sigma;
+
+        Self {
+            albedo,
+            a: 1.0 - sigma_squared / (2.0 * (sigma_squared + 0.33)),
+            b: 0.45 * sigma_squared / (sigma_squared + 0.09),
+        }
+    }
+
+    /// Reflectance relative to a Lambertian surface for the pair of local directions.
+    fn factor(&self, outgoing: Vec3, incoming: Vec3) -> f32 {
+        let sin_outgoing = (1.0 - outgoing.z * outgoing.z).max(0.0).sqrt();
+        let sin_incoming = (1.0 - incoming.z * incoming.z).max(0.0).sqrt();
+
+        let cos_phi = if sin_outgoing > 1e-4 && sin_incoming > 1e-4 {
+            ((outgoing.x * incoming.x + outgoing.y * incoming.y) / (sin_outgoing * sin_incoming))
+                .max(0.0)
+        } else {
+            0.0
+        };
+
+        // Alpha is the larger of both angles, beta the smaller one
+        let (sin_alpha, tan_beta) = if incoming.z.abs() > outgoing.z.abs() {
+            (sin_outgoing, sin_incoming / incoming.z.abs())
+        } else {
+            (sin_incoming, sin_outgoing / outgoing.z.abs())
+        };
+
+        self.a + self.b * cos_phi * sin_alpha * tan_beta
+    }
+}
+
+impl<T: Texture> Material for OrenNayar<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let mut scatter_direction = hit_record.normal + math::random_unit_vector();
+        if math::near_zero(scatter_direction) {
+            scatter_direction = hit_record.normal;
+        }
+
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(scatter_direction.normalize());
+
+        Some(ScatterRecord::new(
+            self.albedo
+                .value(hit_record.u, hit_record.v, hit_record.point)
+                * self.factor(outgoing, incoming),
+            Ray::new(hit_record.point, scatter_direction, ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if incoming.z <= 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        self.albedo
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            * (self.factor(outgoing, incoming) * incoming.z / PI)
+    }
+
+    fn pdf(&self, _ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        hit_record.normal.dot(direction.normalize()).max(0.0) / PI
+    }
+}
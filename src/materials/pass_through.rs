@@ -0,0 +1,35 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::Vec3,
+    ray::Ray,
+};
+
+/// Invisible surface which only marks the boundary between two media.
+#[derive(Clone)]
+pub(crate) struct PassThrough;
+
+impl PassThrough {
+    pub(crate) fn new() -> Self {
+        Self
+    }
+}
+
+impl Material for PassThrough {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        Some(ScatterRecord::specular(
+            Vec3::new(1.0, 1.0, 1.0),
+            Ray::new(hit_record.point, ray.direction(), ray.time()),
+        ))
+    }
+
+    fn is_interface(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,497 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    microfacet::{Ggx, MicrofacetDistribution},
+    onb::Onb,
+    ray::Ray,
+    sampler,
+    textures::{solid_color::SolidColor, Texture},
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::{f32::consts::PI, sync::Arc};
+
+/// Probabilities of the lobes for one outgoing direction, they always add up to one so
+/// picking a lobe by them never creates energy.
+struct Lobes {
+    base_color: Vec3,
+    sheen: f32,
+    diffuse: f32,
+    specular: f32,
+    metallic: f32,
+    clearcoat: f32,
+    distribution: Ggx,
+    clearcoat_distribution: Ggx,
+}
+
+/// Disney style uber material. Every parameter can be driven by a texture, scalar
+/// parameters are read from its first channel.
+#[derive(Clone)]
+pub(crate) struct Principled {
+    base_color: Arc<dyn Texture>,
+    metallic: Arc<dyn Texture>,
+    roughness: Arc<dyn Texture>,
+    specular: Arc<dyn Texture>,
+    sheen: Arc<dyn Texture>,
+    clearcoat: Arc<dyn Texture>,
+    transmission: Arc<dyn Texture>,
+    emission: Arc<dyn Texture>,
+}
+
+impl Principled {
+    const INDEX_OF_REFRACTION: f32 = 1.5;
+    const CLEARCOAT_ROUGHNESS: f32 = 0.1;
+
+    pub(crate) fn new(base_color: impl Texture + 'static) -> Self {
+        let constant = |value| Arc::new(SolidColor::new(Vec3::new(value, value, value)));
+        Self {
+            base_color: Arc::new(base_color),
+            metallic: constant(0.0),
+            roughness: constant(0.5),
+            specular: constant(0.5),
+            sheen: constant(0.0),
+            clearcoat: constant(0.0),
+            transmission: constant(0.0),
+            emission: constant(0.0),
+        }
+    }
+
+    pub(crate) fn with_metallic(self, metallic: impl Texture + 'static) -> Self {
+        Self {
+            metallic: Arc::new(metallic),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_roughness(self, roughness: impl Texture + 'static) -> Self {
+        Self {
+            roughness: Arc::new(roughness),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_specular(self, specular: impl Texture + 'static) -> Self {
+        Self {
+            specular: Arc::new(specular),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_sheen(self, sheen: impl Texture + 'static) -> Self {
+        Self {
+            sheen: Arc::new(sheen),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_clearcoat(self, clearcoat: impl Texture + 'static) -> Self {
+        Self {
+            clearcoat: Arc::new(clearcoat),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_transmission(self, transmission: impl Texture + 'static) -> Self {
+        Self {
+            transmission: Arc::new(transmission),
+            ..self
+        }
+    }
+
+    pub(crate) fn with_emission(self, emission: impl Texture + 'static) -> Self {
+        Self {
+            emission: Arc::new(emission),
+            ..self
+        }
+    }
+
+    fn lobes(&self, hit_record: &HitRecord, cos_outgoing: f32) -> Lobes {
+        let value = |texture: &Arc<dyn Texture>| {
+            texture.value(hit_record.u, hit_record.v, hit_record.point)
+        };
+        let scalar = |texture: &Arc<dyn Texture>| value(texture).x.clamp(0.0, 1.0);
+
+        let clearcoat = scalar(&self.clearcoat)
+            * math::fresnel_dielectric(cos_outgoing, Self::INDEX_OF_REFRACTION);
+        let metallic = (1.0 - clearcoat) * scalar(&self.metallic);
+        let dielectric = 1.0 - clearcoat - metallic;
+
+        let reflectance = 0.08 * scalar(&self.specular);
+        let specular =
+            dielectric * (reflectance + (1.0 - reflectance) * (1.0 - cos_outgoing).powi(5));
+        let transmission = (dielectric - specular) * scalar(&self.transmission);
+
+        Lobes {
+            base_color: value(&self.base_color).map(|channel| channel.clamp(0.0, 1.0)),
+            sheen: scalar(&self.sheen),
+            diffuse: dielectric - specular - transmission,
+            specular,
+            metallic,
+            clearcoat,
+            distribution: Ggx::from_roughness(scalar(&self.roughness)),
+            clearcoat_distribution: Ggx::from_roughness(Self::CLEARCOAT_ROUGHNESS),
+        }
+    }
+
+    fn diffuse(lobes: &Lobes, outgoing: Vec3, incoming: Vec3) -> Vec3 {
+        // Sheen brightens grazing angles by taking energy from the diffuse base
+        let half = (outgoing + incoming).normalize();
+        let sheen = lobes.sheen * (1.0 - incoming.dot(half).clamp(0.0, 1.0)).powi(5);
+        lobes.base_color * (1.0 - sheen) + Vec3::new(sheen, sheen, sheen)
+    }
+
+    fn metallic_fresnel(lobes: &Lobes, cosine: f32) -> Vec3 {
+        let schlick = (1.0 - cosine.clamp(0.0, 1.0)).powi(5);
+        lobes.base_color + (Vec3::new(1.0, 1.0, 1.0) - lobes.base_color) * schlick
+    }
+
+    /// Back faces only lead into the object if it transmits light, opaque surfaces shade
+    /// them like front faces around the flipped normal.
+    fn is_interior(&self, hit_record: &HitRecord) -> bool {
+        !hit_record.front_face
+            && self
+                .transmission
+                .value(hit_record.u, hit_record.v, hit_record.point)
+                .x
+                > 0.0
+    }
+
+    fn eta(hit_record: &HitRecord) -> f32 {
+        if hit_record.front_face {
+            Self::INDEX_OF_REFRACTION
+        } else {
+            1.0 / Self::INDEX_OF_REFRACTION
+        }
+    }
+
+    /// Refracts through the microfacet, `None` if the direction ends up on the wrong side.
+    fn refract(outgoing: Vec3, normal: Vec3, eta: f32) -> Option<Vec3> {
+        let incoming = math::refract(-outgoing, normal, 1.0 / eta).normalize();
+        (incoming.z < 0.0 && incoming.dot(normal) < 0.0).then_some(incoming)
+    }
+
+    /// Sampling visible normals leaves only the shadowing term in the weight.
+    fn shadowing(distribution: &Ggx, outgoing: Vec3, incoming: Vec3) -> f32 {
+        distribution.masking_shadowing(outgoing, incoming) / distribution.masking(outgoing)
+    }
+
+    fn reflection(distribution: &Ggx, outgoing: Vec3, incoming: Vec3) -> f32 {
+        let half = (outgoing + incoming).normalize();
+        distribution.distribution(half) * distribution.masking_shadowing(outgoing, incoming)
+            / (4.0 * outgoing.z)
+    }
+
+    fn transmission(distribution: &Ggx, outgoing: Vec3, incoming: Vec3, eta: f32) -> f32 {
+        let mut half = (outgoing + incoming * eta).normalize();
+        if half.z < 0.0 {
+            half = -half;
+        }
+
+        let cos_outgoing = outgoing.dot(half);
+        let cos_incoming = incoming.dot(half);
+        if cos_outgoing <= 0.0 {
+            return 0.0;
+        }
+
+        let denominator = cos_incoming * eta + cos_outgoing;
+        distribution.distribution(half)
+            * distribution.masking_shadowing(outgoing, incoming)
+            * eta
+            * eta
+            * (cos_incoming * cos_outgoing).abs()
+            / (outgoing.z * denominator * denominator)
+    }
+
+    /// Density of reflecting into `incoming` about a sampled visible normal.
+    fn reflection_pdf(distribution: &Ggx, outgoing: Vec3, incoming: Vec3) -> f32 {
+        let half = (outgoing + incoming).normalize();
+        let cosine = outgoing.dot(half);
+        if cosine <= 0.0 {
+            return 0.0;
+        }
+
+        distribution.pdf(outgoing, half) / (4.0 * cosine)
+    }
+
+    /// Density of refracting into `incoming` through a sampled visible normal.
+    fn transmission_pdf(distribution: &Ggx, outgoing: Vec3, incoming: Vec3, eta: f32) -> f32 {
+        let mut half = (outgoing + incoming * eta).normalize();
+        if half.z < 0.0 {
+            half = -half;
+        }
+
+        let cos_outgoing = outgoing.dot(half);
+        let cos_incoming = incoming.dot(half);
+        if cos_outgoing <= 0.0 || cos_incoming >= 0.0 {
+            return 0.0;
+        }
+
+        let denominator = cos_incoming * eta + cos_outgoing;
+        distribution.pdf(outgoing, half) * eta * eta * cos_incoming.abs()
+            / (denominator * denominator)
+    }
+}
+
+impl Material for Principled {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let lobes = self.lobes(hit_record, outgoing.z);
+        let eta = Self::eta(hit_record);
+        let mut rand = sampler::rng();
+
+        // Rays inside a transmissive object only see the dielectric interface
+        if self.is_interior(hit_record) {
+            let normal = lobes
+                .distribution
+                .sample_normal(outgoing, rand.gen(), rand.gen());
+            let reflectance = math::fresnel_dielectric(outgoing.dot(normal), eta);
+            let incoming = if rand.gen::<f32>() < reflectance {
+                math::reflect(-outgoing, normal)
+            } else {
+                Self::refract(outgoing, normal, eta)?
+            };
+
+            let shadowing = Self::shadowing(&lobes.distribution, outgoing, incoming);
+            return Some(ScatterRecord::new(
+                Vec3::new(shadowing, shadowing, shadowing),
+                Ray::new(hit_record.point, onb.local(incoming), ray.time()),
+            ));
+        }
+
+        let specular_end = lobes.diffuse + lobes.specular;
+        let metallic_end = specular_end + lobes.metallic;
+        let clearcoat_end = metallic_end + lobes.clearcoat;
+
+        let sample = rand.gen::<f32>();
+        let (incoming, weight) = if sample < lobes.diffuse {
+            let incoming = (Vec3::new(0.0, 0.0, 1.0) + math::random_unit_vector()).normalize();
+            if incoming.z <= 0.0 {
+                return None;
+            }
+
+            (incoming, Self::diffuse(&lobes, outgoing, incoming))
+        } else if sample < clearcoat_end {
+            let distribution = if sample < metallic_end {
+                lobes.distribution
+            } else {
+                lobes.clearcoat_distribution
+            };
+
+            let normal = distribution.sample_normal(outgoing, rand.gen(), rand.gen());
+            let incoming = math::reflect(-outgoing, normal);
+            if incoming.z <= 0.0 {
+                return None;
+            }
+
+            let tint = if sample >= specular_end && sample < metallic_end {
+                Self::metallic_fresnel(&lobes, outgoing.dot(normal))
+            } else {
+                Vec3::new(1.0, 1.0, 1.0)
+            };
+
+            (
+                incoming,
+                tint * Self::shadowing(&distribution, outgoing, incoming),
+            )
+        } else {
+            let normal = lobes
+                .distribution
+                .sample_normal(outgoing, rand.gen(), rand.gen());
+            let incoming = Self::refract(outgoing, normal, eta)?;
+
+            (
+                incoming,
+                lobes.base_color * Self::shadowing(&lobes.distribution, outgoing, incoming),
+            )
+        };
+
+        Some(ScatterRecord::new(
+            weight,
+            Ray::new(hit_record.point, onb.local(incoming), ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let lobes = self.lobes(hit_record, outgoing.z);
+        let eta = Self::eta(hit_record);
+
+        if self.is_interior(hit_record) {
+            let value = if incoming.z > 0.0 {
+                let half = (outgoing + incoming).normalize();
+                math::fresnel_dielectric(outgoing.dot(half), eta)
+                    * Self::reflection(&lobes.distribution, outgoing, incoming)
+            } else {
+                let mut half = (outgoing + incoming * eta).normalize();
+                if half.z < 0.0 {
+                    half = -half;
+                }
+
+                (1.0 - math::fresnel_dielectric(outgoing.dot(half), eta))
+                    * Self::transmission(&lobes.distribution, outgoing, incoming, eta)
+            };
+
+            return Vec3::new(value, value, value);
+        }
+
+        let transmission = 1.0 - lobes.diffuse - lobes.specular - lobes.metallic - lobes.clearcoat;
+        if incoming.z < 0.0 {
+            return lobes.base_color
+                * (transmission
+                    * Self::transmission(&lobes.distribution, outgoing, incoming, eta));
+        }
+
+        let half = (outgoing + incoming).normalize();
+        let specular = Self::reflection(&lobes.distribution, outgoing, incoming);
+        let clearcoat = Self::reflection(&lobes.clearcoat_distribution, outgoing, incoming);
+
+        Self::diffuse(&lobes, outgoing, incoming) * (lobes.diffuse * incoming.z / PI)
+            + Self::metallic_fresnel(&lobes, outgoing.dot(half)) * (lobes.metallic * specular)
+            + Vec3::new(1.0, 1.0, 1.0) * (lobes.specular * specular + lobes.clearcoat * clearcoat)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return 0.0;
+        }
+
+        let lobes = self.lobes(hit_record, outgoing.z);
+        let eta = Self::eta(hit_record);
+
+        if self.is_interior(hit_record) {
+            let reflection = incoming.z > 0.0;
+            let mut half = if reflection {
+                outgoing + incoming
+            } else {
+                outgoing + incoming * eta
+            }
+            .normalize();
+            if half.z < 0.0 {
+                half = -half;
+            }
+
+            let reflectance = math::fresnel_dielectric(outgoing.dot(half), eta);
+            return if reflection {
+                reflectance * Self::reflection_pdf(&lobes.distribution, outgoing, incoming)
+            } else {
+                (1.0 - reflectance)
+                    * Self::transmission_pdf(&lobes.distribution, outgoing, incoming, eta)
+            };
+        }
+
+        let transmission = 1.0 - lobes.diffuse - lobes.specular - lobes.metallic - lobes.clearcoat;
+        if incoming.z < 0.0 {
+            return transmission
+                * Self::transmission_pdf(&lobes.distribution, outgoing, incoming, eta);
+        }
+
+        lobes.diffuse * incoming.z / PI
+            + (lobes.specular + lobes.metallic)
+                * Self::reflection_pdf(&lobes.distribution, outgoing, incoming)
+            + lobes.clearcoat
+                * Self::reflection_pdf(&lobes.clearcoat_distribution, outgoing, incoming)
+    }
+
+    fn emitted(&self, _ray: &Ray, hit_record: &HitRecord) -> Vec3 {
+        self.emission
+            .value(hit_record.u, hit_record.v, hit_record.point)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Averages the scatter weights of rays arriving from `cos_outgoing`, which is the
+    /// albedo seen under a uniform white environment.
+    fn albedo(material: &Principled, cos_outgoing: f32) -> Vec3 {
+        const SAMPLES: u32 = 200_000;
+
+        let sin_outgoing = (1.0 - cos_outgoing * cos_outgoing).sqrt();
+        let outgoing = Vec3::new(sin_outgoing, 0.0, cos_outgoing);
+        let ray = Ray::new(outgoing, -outgoing, 0.0);
+        let hit_record = HitRecord {
+            point: Vec3::new(0.0, 0.0, 0.0),
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            tangent: Vec3::new(1.0, 0.0, 0.0),
+            bitangent: Vec3::new(0.0, 1.0, 0.0),
+            t: 1.0,
+            u: 0.0,
+            v: 0.0,
+            front_face: true,
+            material,
+            medium_interface: None,
+        };
+
+        let total = (0..SAMPLES)
+            .filter_map(|_| material.scatter(&ray, &hit_record))
+            .fold(Vec3::new(0.0, 0.0, 0.0), |total, record| {
+                total + record.attenuation
+            });
+        total / SAMPLES as f32
+    }
+
+    #[test]
+    fn furnace_does_not_create_energy() {
+        let white = || SolidColor::new(Vec3::new(1.0, 1.0, 1.0));
+        let constant = |value| SolidColor::new(Vec3::new(value, value, value));
+        let materials = [
+            (
+                "diffuse",
+                Principled::new(white()).with_roughness(constant(1.0)),
+            ),
+            (
+                "metallic",
+                Principled::new(white())
+                    .with_metallic(constant(1.0))
+                    .with_roughness(constant(0.3)),
+            ),
+            (
+                "clearcoat",
+                Principled::new(white())
+                    .with_clearcoat(constant(1.0))
+                    .with_sheen(constant(1.0)),
+            ),
+            (
+                "transmissive",
+                Principled::new(white())
+                    .with_transmission(constant(1.0))
+                    .with_roughness(constant(0.2)),
+            ),
+        ];
+
+        for (name, material) in &materials {
+            for cos_outgoing in [0.95, 0.6, 0.2] {
+                let albedo = albedo(material, cos_outgoing);
+                for channel in [albedo.x, albedo.y, albedo.z] {
+                    assert!(
+                        channel <= 1.01,
+                        "{name} albedo {channel} at cosine {cos_outgoing} exceeds one"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{dielectric::Dielectric, Material, ScatterRecord},
+    math::{self, Vec3},
+    microfacet::MicrofacetDistribution,
+    onb::Onb,
+    ray::Ray,
+    sampler,
+    textures::Texture,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+use std::marker::PhantomData;
+
+/// Frosted glass following the microfacet transmission model by Walter et al. The
+/// roughness is read from the first channel of the texture, smooth spots fall back to the
+/// perfectly specular dielectric.
+#[derive(Clone)]
+pub(crate) struct RoughDielectric<D: MicrofacetDistribution, T: Texture> {
+    index_of_refraction: f32,
+    roughness: T,
+    smooth: Dielectric,
+    _phantom: PhantomData<D>,
+}
+
+impl<D: MicrofacetDistribution, T: Texture> RoughDielectric<D, T> {
+    pub(crate) fn new(index_of_refraction: f32, roughness: T) -> Self {
+        Self {
+            index_of_refraction,
+            roughness,
+            smooth: Dielectric::new(index_of_refraction),
+            _phantom: PhantomData,
+        }
+    }
+
+    fn distribution(&self, hit_record: &HitRecord) -> D {
+        let roughness = self
+            .roughness
+            .value(hit_record.u, hit_record.v, hit_record.point);
+        D::from_roughness(roughness.x.clamp(0.0, 1.0))
+    }
+
+    /// Ratio of the index of refraction behind the surface over the one in front of it.
+    fn eta(&self, hit_record: &HitRecord) -> f32 {
+        if hit_record.front_face {
+            self.index_of_refraction
+        } else {
+            1.0 / self.index_of_refraction
+        }
+    }
+}
+
+impl<D: MicrofacetDistribution, T: Texture> Material for RoughDielectric<D, T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let distribution = self.distribution(hit_record);
+        if distribution.is_smooth() {
+            return self.smooth.scatter(ray, hit_record);
+        }
+
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        if outgoing.z <= 0.0 {
+            return None;
+        }
+
+        let mut rand = sampler::rng();
+        let normal = distribution.sample_normal(outgoing, rand.gen(), rand.gen());
+        let cos_outgoing = outgoing.dot(normal);
+        if cos_outgoing <= 0.0 {
+            return None;
+        }
+
+        let eta = self.eta(hit_record);
+        let reflectance = math::fresnel_dielectric(cos_outgoing, eta);
+
+        // Choosing the lobe by the Fresnel term cancels it out of the weight
+        let incoming = if rand.gen::<f32>() < reflectance {
+            let incoming = math::reflect(-outgoing, normal);
+            if incoming.z <= 0.0 {
+                return None;
+            }
+
+            incoming
+        } else {
+            let incoming = math::refract(-outgoing, normal, 1.0 / eta).normalize();
+            if incoming.z >= 0.0 || incoming.dot(normal) >= 0.0 {
+                return None;
+            }
+
+            incoming
+        };
+
+        let weight = distribution.distribution(normal)
+            * distribution.masking_shadowing(outgoing, incoming)
+            * cos_outgoing
+            / (outgoing.z * distribution.pdf(outgoing, normal));
+        if !weight.is_finite() {
+            return None;
+        }
+
+        Some(ScatterRecord::new(
+            Vec3::new(weight, weight, weight),
+            Ray::new(hit_record.point, onb.local(incoming), ray.time()),
+        ))
+    }
+
+    fn evaluate(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> Vec3 {
+        let distribution = self.distribution(hit_record);
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if distribution.is_smooth() || outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let eta = self.eta(hit_record);
+        let reflection = incoming.z > 0.0;
+
+        // Generalized half vector, facing the side of the outgoing direction
+        let mut normal = if reflection {
+            outgoing + incoming
+        } else {
+            outgoing + incoming * eta
+        }
+        .normalize();
+        if normal.z < 0.0 {
+            normal = -normal;
+        }
+
+        let cos_outgoing = outgoing.dot(normal);
+        let cos_incoming = incoming.dot(normal);
+        if cos_outgoing <= 0.0 || (reflection && cos_incoming <= 0.0) {
+            return Vec3::new(0.0, 0.0, 0.0);
+        }
+
+        let reflectance = math::fresnel_dielectric(cos_outgoing, eta);
+        let microfacets =
+            distribution.distribution(normal) * distribution.masking_shadowing(outgoing, incoming);
+        let value = if reflection {
+            reflectance * microfacets / (4.0 * outgoing.z)
+        } else {
+            let denominator = cos_incoming * eta + cos_outgoing;
+            (1.0 - reflectance) * microfacets * eta * eta * (cos_incoming * cos_outgoing).abs()
+                / (outgoing.z * denominator * denominator)
+        };
+
+        Vec3::new(value, value, value)
+    }
+
+    fn pdf(&self, ray: &Ray, hit_record: &HitRecord, direction: Vec3) -> f32 {
+        let distribution = self.distribution(hit_record);
+        let onb = Onb::from_w(hit_record.normal);
+        let outgoing = onb.world_to_local(-ray.direction().normalize());
+        let incoming = onb.world_to_local(direction.normalize());
+        if distribution.is_smooth() || outgoing.z <= 0.0 || incoming.z == 0.0 {
+            return 0.0;
+        }
+
+        let eta = self.eta(hit_record);
+        let reflection = incoming.z > 0.0;
+
+        let mut normal = if reflection {
+            outgoing + incoming
+        } else {
+            outgoing + incoming * eta
+        }
+        .normalize();
+        if normal.z < 0.0 {
+            normal = -normal;
+        }
+
+        let cos_outgoing = outgoing.dot(normal);
+        let cos_incoming = incoming.dot(normal);
+        if cos_outgoing <= 0.0 || (cos_incoming > 0.0) != reflection {
+            return 0.0;
+        }
+
+        // Densities of the microfacet normal mapped to the reflected or refracted direction
+        let reflectance = math::fresnel_dielectric(cos_outgoing, eta);
+        let normal_pdf = distribution.pdf(outgoing, normal);
+        if reflection {
+            reflectance * normal_pdf / (4.0 * cos_outgoing)
+        } else {
+            let denominator = cos_incoming * eta + cos_outgoing;
+            (1.0 - reflectance) * normal_pdf * eta * eta * cos_incoming.abs()
+                / (denominator * denominator)
+        }
+    }
+}
@@ -0,0 +1,302 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord,
+    materials::{Material, ScatterRecord},
+    math::{self, Vec3},
+    ray::Ray,
+    sampler, spectrum,
+    textures::Texture,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use rand::Rng;
+use std::{
+    f32::consts::PI,
+    ops::{Add, Div, Mul, Sub},
+};
+
+#[derive(Clone, Copy, Debug)]
+struct Complex {
+    re: f32,
+    im: f32,
+}
+
+impl Complex {
+    fn new(re: f32, im: f32) -> Self {
+        Self { re, im }
+    }
+
+    fn real(re: f32) -> Self {
+        Self::new(re, 0.0)
+    }
+
+    fn norm_squared(self) -> f32 {
+        self.re * self.re + self.im * self.im
+    }
+
+    fn sqrt(self) -> Self {
+        let norm = self.norm_squared().sqrt();
+        let re = (0.5 * (norm + self.re)).max(0.0).sqrt();
+        let im = (0.5 * (norm - self.re)).max(0.0).sqrt();
+        Self::new(re, if self.im < 0.0 { -im } else { im })
+    }
+
+    /// Computes `e^(i z)`.
+    fn exp_i(self) -> Self {
+        let magnitude = (-self.im).exp();
+        Self::new(magnitude * self.re.cos(), magnitude * self.re.sin())
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        let denominator = other.norm_squared();
+        Self::new(
+            (self.re * other.re + self.im * other.im) / denominator,
+            (self.im * other.re - self.re * other.im) / denominator,
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Substrate {
+    Dielectric(f32),
+    Metal { eta: Vec3, k: Vec3 },
+}
+
+impl Substrate {
+    /// Index of refraction at `wavelength`, metals are given at 650nm, 550nm and 450nm
+    /// per color channel and interpolated linearly between them.
+    fn index_of_refraction(&self, wavelength: f32) -> Complex {
+        let interpolate = |values: Vec3| {
+            let t = ((wavelength - 450.0) / 100.0).clamp(0.0, 2.0);
+            if t < 1.0 {
+                values.z + (values.y - values.z) * t
+            } else {
+                values.y + (values.x - values.y) * (t - 1.0)
+            }
+        };
+
+        match *self {
+            Self::Dielectric(index) => Complex::real(index),
+            Self::Metal { eta, k } => Complex::new(interpolate(eta), interpolate(k)),
+        }
+    }
+}
+
+/// Iridescent coating of a thin film on top of a smooth dielectric or metal. The film
+/// thickness in nanometers is the first channel of the texture times `thickness_scale`.
+#[derive(Clone)]
+pub(crate) struct ThinFilm<T: Texture> {
+    substrate: Substrate,
+    film_index: f32,
+    thickness: T,
+    thickness_scale: f32,
+}
+
+impl<T: Texture> ThinFilm<T> {
+    pub(crate) fn dielectric(
+        index_of_refraction: f32,
+        film_index: f32,
+        thickness: T,
+        thickness_scale: f32,
+    ) -> Self {
+        Self {
+            substrate: Substrate::Dielectric(index_of_refraction),
+            film_index,
+            thickness,
+            thickness_scale,
+        }
+    }
+
+    /// Film on a metal with the complex index of refraction `eta + i k` per color channel.
+    pub(crate) fn metal(
+        eta: Vec3,
+        k: Vec3,
+        film_index: f32,
+        thickness: T,
+        thickness_scale: f32,
+    ) -> Self {
+        Self {
+            substrate: Substrate::Metal { eta, k },
+            film_index,
+            thickness,
+            thickness_scale,
+        }
+    }
+
+    /// Reflectance of the film stack following the Airy summation of all internal
+    /// reflections, averaged over both polarizations.
+    fn reflectance(
+        &self,
+        cosine: f32,
+        incident: f32,
+        transmitted: Complex,
+        thickness: f32,
+        wavelength: f32,
+    ) -> f32 {
+        let incident = Complex::real(incident);
+        let film = Complex::real(self.film_index);
+        let sin_squared = incident * incident * Complex::real(1.0 - cosine * cosine);
+
+        let cos_incident = Complex::real(cosine);
+        let cos_film = (Complex::real(1.0) - sin_squared / (film * film)).sqrt();
+        let cos_transmitted =
+            (Complex::real(1.0) - sin_squared / (transmitted * transmitted)).sqrt();
+
+        // Phase difference between two consecutive paths through the film
+        let phase = Complex::real(4.0 * PI * thickness / wavelength) * film * cos_film;
+        let shift = phase.exp_i();
+
+        let airy = |first: Complex, second: Complex| {
+            ((first + second * shift) / (Complex::real(1.0) + first * second * shift))
+                .norm_squared()
+        };
+
+        let perpendicular = airy(
+            (incident * cos_incident - film * cos_film)
+                / (incident * cos_incident + film * cos_film),
+            (film * cos_film - transmitted * cos_transmitted)
+                / (film * cos_film + transmitted * cos_transmitted),
+        );
+        let parallel = airy(
+            (film * cos_incident - incident * cos_film)
+                / (film * cos_incident + incident * cos_film),
+            (transmitted * cos_film - film * cos_transmitted)
+                / (transmitted * cos_film + film * cos_transmitted),
+        );
+
+        (0.5 * (perpendicular + parallel)).clamp(0.0, 1.0)
+    }
+}
+
+impl<T: Texture> Material for ThinFilm<T> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterRecord> {
+        let unit_direction = ray.direction().normalize();
+        let cos_theta = (-unit_direction).dot(hit_record.normal).min(1.0);
+        let thickness = self
+            .thickness
+            .value(hit_record.u, hit_record.v, hit_record.point)
+            .x
+            .max(0.0)
+            * self.thickness_scale;
+
+        let (incident, transmitted) = match self.substrate {
+            Substrate::Dielectric(index) if !hit_record.front_face => {
+                (index, Substrate::Dielectric(1.0))
+            }
+            substrate => (1.0, substrate),
+        };
+
+        let reflectance = |wavelength: f32| {
+            self.reflectance(
+                cos_theta,
+                incident,
+                transmitted.index_of_refraction(wavelength),
+                thickness,
+                wavelength,
+            )
+        };
+
+        let reflected = Ray::new(
+            hit_record.point,
+            math::reflect(unit_direction, hit_record.normal),
+            ray.time(),
+        );
+
+        let Substrate::Dielectric(index) = transmitted else {
+            return Some(match ray.wavelength() {
+                Some(wavelength) => {
+                    let reflectance = reflectance(wavelength);
+                    ScatterRecord::dispersive(
+                        Vec3::new(reflectance, reflectance, reflectance),
+                        reflected,
+                    )
+                }
+                None => {
+                    ScatterRecord::specular(spectrum::reflectance_to_rgb(reflectance), reflected)
+                }
+            });
+        };
+
+        // The film is too thin to offset the ray, it refracts as if the substrate was bare
+        let refraction_ratio = incident / index;
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let refracted = || {
+            Ray::new(
+                hit_record.point,
+                math::refract(unit_direction, hit_record.normal, refraction_ratio),
+                ray.time(),
+            )
+        };
+
+        if refraction_ratio * sin_theta > 1.0 {
+            return Some(ScatterRecord::specular(Vec3::new(1.0, 1.0, 1.0), reflected));
+        }
+
+        let mut rand = sampler::rng();
+
+        match ray.wavelength() {
+            Some(wavelength) => {
+                let ray = if rand.gen::<f32>() < reflectance(wavelength) {
+                    reflected
+                } else {
+                    refracted()
+                };
+                Some(ScatterRecord::dispersive(Vec3::new(1.0, 1.0, 1.0), ray))
+            }
+            None => {
+                let reflectance =
+                    spectrum::reflectance_to_rgb(reflectance).map(|value| value.clamp(0.0, 1.0));
+                let probability =
+                    ((reflectance.x + reflectance.y + reflectance.z) / 3.0).clamp(0.01, 0.99);
+                if rand.gen::<f32>() < probability {
+                    Some(ScatterRecord::specular(
+                        reflectance / probability,
+                        reflected,
+                    ))
+                } else {
+                    let transmittance = Vec3::new(1.0, 1.0, 1.0).sub_element_wise(reflectance);
+                    Some(ScatterRecord::specular(
+                        transmittance / (1.0 - probability),
+                        refracted(),
+                    ))
+                }
+            }
+        }
+    }
+}
@@ -4,6 +4,8 @@
  * SPDX-License-Identifier: MIT
  */
 
+use crate::sampler;
+
 use cgmath::{InnerSpace, Vector3};
 use rand::{distributions::Uniform, prelude::Distribution, Rng};
 
@@ -14,7 +16,7 @@ pub(crate) fn random_unit_vector() -> Vec3 {
 }
 
 pub(crate) fn random_in_unit_sphere() -> Vec3 {
-    let mut rand = rand::thread_rng();
+    let mut rand = sampler::rng();
     let range = Uniform::from(-1.0..1.0);
     loop {
         let point = Vec3::new(
@@ -30,7 +32,7 @@ pub(crate) fn random_in_unit_sphere() -> Vec3 {
 }
 
 pub(crate) fn random_in_unit_disk() -> Vec3 {
-    let mut rand = rand::thread_rng();
+    let mut rand = sampler::rng();
     loop {
         let point = Vec3::new(rand.gen_range(-1.0..1.0), rand.gen_range(-1.0..1.0), 0.0);
         if point.dot(point) < 1.0 {
@@ -39,6 +41,10 @@ pub(crate) fn random_in_unit_disk() -> Vec3 {
     }
 }
 
+pub(crate) fn luminance(color: Vec3) -> f32 {
+    0.2126 * color.x + 0.7152 * color.y + 0.0722 * color.z
+}
+
 pub(crate) fn near_zero(vector: Vec3) -> bool {
     const S: f32 = 1e-8;
     (vector.x.abs() < S) && (vector.y.abs() < S) && (vector.z.abs() < S)
@@ -60,3 +66,47 @@ pub(crate) fn reflectance(cosine: f32, refraction_index: f32) -> f32 {
     r0 = r0 * r0;
     r0 + (1.0 - r0) * (1.0 - cosine).powf(5.0)
 }
+
+/// Exact Fresnel reflectance of a dielectric interface, `eta` is the ratio of the index of
+/// refraction on the transmitted side over the one on the incident side.
+pub(crate) fn fresnel_dielectric(cosine: f32, eta: f32) -> f32 {
+    let cosine = cosine.clamp(0.0, 1.0);
+    let sin_transmitted_squared = (1.0 - cosine * cosine) / (eta * eta);
+    if sin_transmitted_squared >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_transmitted = (1.0 - sin_transmitted_squared).sqrt();
+    let parallel = (eta * cosine - cos_transmitted) / (eta * cosine + cos_transmitted);
+    let perpendicular = (cosine - eta * cos_transmitted) / (cosine + eta * cos_transmitted);
+    0.5 * (parallel * parallel + perpendicular * perpendicular)
+}
+
+/// Fresnel reflectance of a conductor with the complex index of refraction `eta + i k`.
+pub(crate) fn fresnel_conductor(cosine: f32, eta: Vec3, k: Vec3) -> Vec3 {
+    let cosine = cosine.clamp(0.0, 1.0);
+    let cos_squared = cosine * cosine;
+    let sin_squared = 1.0 - cos_squared;
+
+    let channel = |eta: f32, k: f32| {
+        let t0 = eta * eta - k * k - sin_squared;
+        let a_squared_plus_b_squared = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let a = (0.5 * (a_squared_plus_b_squared + t0)).max(0.0).sqrt();
+
+        let t1 = a_squared_plus_b_squared + cos_squared;
+        let t2 = 2.0 * cosine * a;
+        let perpendicular = (t1 - t2) / (t1 + t2);
+
+        let t3 = cos_squared * a_squared_plus_b_squared + sin_squared * sin_squared;
+        let t4 = t2 * sin_squared;
+        let parallel = perpendicular * (t3 - t4) / (t3 + t4);
+
+        0.5 * (parallel + perpendicular)
+    };
+
+    Vec3::new(
+        channel(eta.x, k.x),
+        channel(eta.y, k.y),
+        channel(eta.z, k.z),
+    )
+}
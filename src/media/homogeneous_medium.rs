@@ -0,0 +1,74 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord, materials::Material, math::Vec3, media::Medium, ray::Ray, sampler,
+};
+
+use cgmath::InnerSpace;
+use rand::Rng;
+
+/// Medium with constant coefficients. Absorption is given per color channel and applied
+/// following the Beer-Lambert law, scattering events are sampled by distance.
+pub(crate) struct HomogeneousMedium<M: Material> {
+    absorption: Vec3,
+    scattering: f32,
+    phase_function: M,
+}
+
+impl<M: Material> HomogeneousMedium<M> {
+    pub(crate) fn new(absorption: Vec3, scattering: f32, phase_function: M) -> Self {
+        Self {
+            absorption,
+            scattering,
+            phase_function,
+        }
+    }
+
+    fn absorb(&self, distance: f32) -> Vec3 {
+        // Clamping keeps zero absorption from turning an infinite distance into NaN
+        let distance = distance.min(f32::MAX);
+        Vec3::new(
+            (-self.absorption.x * distance).exp(),
+            (-self.absorption.y * distance).exp(),
+            (-self.absorption.z * distance).exp(),
+        )
+    }
+}
+
+impl<M: Material> Medium for HomogeneousMedium<M> {
+    fn sample(&self, ray: &Ray, time_max: f32) -> (Option<HitRecord>, Vec3) {
+        let ray_length = ray.direction().magnitude();
+        let distance_to_surface = time_max * ray_length;
+
+        let mut rand = sampler::rng();
+        let distance = if self.scattering > 0.0 {
+            -(1.0 - rand.gen::<f32>()).ln() / self.scattering
+        } else {
+            f32::INFINITY
+        };
+
+        if distance >= distance_to_surface {
+            return (None, self.absorb(distance_to_surface));
+        }
+
+        let t = distance / ray_length;
+        let hit_record = HitRecord {
+            point: ray.at(t),
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent: Vec3::new(0.0, 0.0, 0.0),
+            bitangent: Vec3::new(0.0, 0.0, 0.0),
+            t,
+            u: 0.0,
+            v: 0.0,
+            front_face: false,
+            material: &self.phase_function,
+            medium_interface: None,
+        };
+
+        (Some(hit_record), self.absorb(distance))
+    }
+}
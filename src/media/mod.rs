@@ -0,0 +1,83 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{hit_record::HitRecord, math::Vec3, ray::Ray};
+
+use cgmath::InnerSpace;
+use std::sync::Arc;
+
+pub(crate) mod homogeneous_medium;
+pub(crate) mod subsurface_medium;
+
+pub(crate) trait Medium: Send + Sync {
+    /// Samples a scattering event along the ray before `time_max`. The returned weight
+    /// accounts for the absorption along the travelled distance.
+    fn sample(&self, ray: &Ray, time_max: f32) -> (Option<HitRecord>, Vec3);
+}
+
+/// Media on both sides of a surface, `None` stands for vacuum.
+#[derive(Clone)]
+pub(crate) struct MediumInterface {
+    interior: Option<Arc<dyn Medium>>,
+    exterior: Option<Arc<dyn Medium>>,
+}
+
+impl MediumInterface {
+    pub(crate) fn new(
+        interior: Option<Arc<dyn Medium>>,
+        exterior: Option<Arc<dyn Medium>>,
+    ) -> Self {
+        Self { interior, exterior }
+    }
+
+    pub(crate) fn interior(&self) -> Option<&dyn Medium> {
+        self.interior.as_deref()
+    }
+
+    pub(crate) fn exterior(&self) -> Option<&dyn Medium> {
+        self.exterior.as_deref()
+    }
+}
+
+/// Finds the next interaction of a ray travelling through `medium`, either a scattering
+/// event inside the medium or the surface hit, together with the transmittance weight.
+pub(crate) fn next_interaction<'a>(
+    medium: Option<&'a dyn Medium>,
+    ray: &Ray,
+    hit_record: Option<HitRecord<'a>>,
+) -> (Option<HitRecord<'a>>, Vec3) {
+    let Some(medium) = medium else {
+        return (hit_record, Vec3::new(1.0, 1.0, 1.0));
+    };
+
+    let time_max = hit_record
+        .as_ref()
+        .map_or(f32::INFINITY, |hit_record| hit_record.t);
+    let (event, weight) = medium.sample(ray, time_max);
+    (event.or(hit_record), weight)
+}
+
+/// Returns the medium a ray continues in after leaving an interaction into `direction`.
+pub(crate) fn next_medium<'a>(
+    medium: Option<&'a dyn Medium>,
+    hit_record: &HitRecord<'a>,
+    direction: Vec3,
+) -> Option<&'a dyn Medium> {
+    let Some(medium_interface) = hit_record.medium_interface else {
+        return medium;
+    };
+
+    // The normal faces the incoming ray, only transmitted rays cross the interface
+    if direction.dot(hit_record.normal) >= 0.0 {
+        return medium;
+    }
+
+    if hit_record.front_face {
+        medium_interface.interior()
+    } else {
+        medium_interface.exterior()
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hit_record::HitRecord, materials::Material, math::Vec3, media::Medium, ray::Ray, sampler,
+};
+
+use cgmath::{ElementWise, InnerSpace};
+use rand::Rng;
+
+/// Dense medium for random walk subsurface scattering, meant to fill a closed dielectric
+/// boundary. The mean free path is given per color channel and the single scattering
+/// albedo is the one of the phase function.
+pub(crate) struct SubsurfaceMedium<M: Material> {
+    extinction: Vec3,
+    phase_function: M,
+}
+
+impl<M: Material> SubsurfaceMedium<M> {
+    pub(crate) fn new(mean_free_path: Vec3, phase_function: M) -> Self {
+        Self {
+            extinction: Vec3::new(
+                1.0 / mean_free_path.x,
+                1.0 / mean_free_path.y,
+                1.0 / mean_free_path.z,
+            ),
+            phase_function,
+        }
+    }
+
+    fn transmittance(&self, distance: f32) -> Vec3 {
+        Vec3::new(
+            (-self.extinction.x * distance).exp(),
+            (-self.extinction.y * distance).exp(),
+            (-self.extinction.z * distance).exp(),
+        )
+    }
+}
+
+impl<M: Material> Medium for SubsurfaceMedium<M> {
+    fn sample(&self, ray: &Ray, time_max: f32) -> (Option<HitRecord>, Vec3) {
+        let ray_length = ray.direction().magnitude();
+        let distance_to_surface = time_max * ray_length;
+
+        // Sample the distance with the extinction of a random channel, the weights divide
+        // by the average probability over all channels
+        let mut rand = sampler::rng();
+        let channel = ((rand.gen::<f32>() * 3.0) as usize).min(2);
+        let distance = -(1.0 - rand.gen::<f32>()).ln() / self.extinction[channel];
+
+        if distance >= distance_to_surface {
+            let transmittance = self.transmittance(distance_to_surface);
+            let probability = (transmittance.x + transmittance.y + transmittance.z) / 3.0;
+            if probability <= 0.0 {
+                return (None, Vec3::new(0.0, 0.0, 0.0));
+            }
+
+            return (None, transmittance / probability);
+        }
+
+        let density = self
+            .extinction
+            .mul_element_wise(self.transmittance(distance));
+        let probability = (density.x + density.y + density.z) / 3.0;
+
+        let t = distance / ray_length;
+        let hit_record = HitRecord {
+            point: ray.at(t),
+            normal: Vec3::new(0.0, 0.0, 0.0),
+            tangent: Vec3::new(0.0, 0.0, 0.0),
+            bitangent: Vec3::new(0.0, 0.0, 0.0),
+            t,
+            u: 0.0,
+            v: 0.0,
+            front_face: false,
+            material: &self.phase_function,
+            medium_interface: None,
+        };
+
+        (Some(hit_record), density / probability)
+    }
+}
@@ -0,0 +1,210 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+use cgmath::InnerSpace;
+use std::f32::consts::PI;
+
+/// Distribution of microfacet normals. All directions are given in the local shading frame
+/// with the macro surface normal along the z axis.
+pub(crate) trait MicrofacetDistribution: Send + Sync {
+    fn new(alpha_x: f32, alpha_y: f32) -> Self
+    where
+        Self: Sized;
+
+    /// Maps the perceptually linear roughness to the width of the distribution.
+    fn from_roughness(roughness: f32) -> Self
+    where
+        Self: Sized,
+    {
+        let alpha = roughness * roughness;
+        Self::new(alpha, alpha)
+    }
+
+    fn alpha(&self) -> (f32, f32);
+
+    /// Surfaces this smooth are better handled as perfect mirrors.
+    fn is_smooth(&self) -> bool {
+        let (alpha_x, alpha_y) = self.alpha();
+        alpha_x.max(alpha_y) < 1e-3
+    }
+
+    fn distribution(&self, normal: Vec3) -> f32;
+
+    fn lambda(&self, direction: Vec3) -> f32;
+
+    fn masking(&self, direction: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(direction))
+    }
+
+    fn masking_shadowing(&self, outgoing: Vec3, incoming: Vec3) -> f32 {
+        1.0 / (1.0 + self.lambda(outgoing) + self.lambda(incoming))
+    }
+
+    fn sample_normal(&self, outgoing: Vec3, u: f32, v: f32) -> Vec3;
+
+    /// Density of `sample_normal` returning `normal` when seen from `outgoing`.
+    fn pdf(&self, outgoing: Vec3, normal: Vec3) -> f32;
+}
+
+/// Trowbridge-Reitz (GGX) distribution, sampled by its visible normals.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Ggx {
+    alpha_x: f32,
+    alpha_y: f32,
+}
+
+impl MicrofacetDistribution for Ggx {
+    fn new(alpha_x: f32, alpha_y: f32) -> Self {
+        Self {
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+        }
+    }
+
+    fn alpha(&self) -> (f32, f32) {
+        (self.alpha_x, self.alpha_y)
+    }
+
+    fn distribution(&self, normal: Vec3) -> f32 {
+        if normal.z <= 0.0 {
+            return 0.0;
+        }
+
+        let x = normal.x / self.alpha_x;
+        let y = normal.y / self.alpha_y;
+        let denominator = x * x + y * y + normal.z * normal.z;
+        1.0 / (PI * self.alpha_x * self.alpha_y * denominator * denominator)
+    }
+
+    fn lambda(&self, direction: Vec3) -> f32 {
+        let cos_theta_squared = direction.z * direction.z;
+        if cos_theta_squared == 0.0 {
+            return f32::INFINITY;
+        }
+
+        let x = self.alpha_x * direction.x;
+        let y = self.alpha_y * direction.y;
+        ((1.0 + (x * x + y * y) / cos_theta_squared).sqrt() - 1.0) / 2.0
+    }
+
+    /// Samples a microfacet normal visible from `outgoing` following Heitz.
+    fn sample_normal(&self, outgoing: Vec3, u: f32, v: f32) -> Vec3 {
+        // Stretch the view direction to the configuration of a hemisphere
+        let view = Vec3::new(
+            self.alpha_x * outgoing.x,
+            self.alpha_y * outgoing.y,
+            outgoing.z,
+        )
+        .normalize();
+
+        let length_squared = view.x * view.x + view.y * view.y;
+        let tangent_1 = if length_squared > 0.0 {
+            Vec3::new(-view.y, view.x, 0.0) / length_squared.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let tangent_2 = view.cross(tangent_1);
+
+        let radius = u.sqrt();
+        let phi = 2.0 * PI * v;
+        let p1 = radius * phi.cos();
+        let p2 = radius * phi.sin();
+        let s = 0.5 * (1.0 + view.z);
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).max(0.0).sqrt() + s * p2;
+
+        let normal =
+            p1 * tangent_1 + p2 * tangent_2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * view;
+
+        Vec3::new(
+            self.alpha_x * normal.x,
+            self.alpha_y * normal.y,
+            normal.z.max(1e-6),
+        )
+        .normalize()
+    }
+
+    fn pdf(&self, outgoing: Vec3, normal: Vec3) -> f32 {
+        if outgoing.z <= 0.0 {
+            return 0.0;
+        }
+
+        self.masking(outgoing) * outgoing.dot(normal).max(0.0) * self.distribution(normal)
+            / outgoing.z
+    }
+}
+
+/// Beckmann distribution, sampled proportional to the projected microfacet area.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Beckmann {
+    alpha_x: f32,
+    alpha_y: f32,
+}
+
+impl MicrofacetDistribution for Beckmann {
+    fn new(alpha_x: f32, alpha_y: f32) -> Self {
+        Self {
+            alpha_x: alpha_x.max(1e-4),
+            alpha_y: alpha_y.max(1e-4),
+        }
+    }
+
+    fn alpha(&self) -> (f32, f32) {
+        (self.alpha_x, self.alpha_y)
+    }
+
+    fn distribution(&self, normal: Vec3) -> f32 {
+        if normal.z <= 0.0 {
+            return 0.0;
+        }
+
+        let cos_theta_squared = normal.z * normal.z;
+        let x = normal.x / self.alpha_x;
+        let y = normal.y / self.alpha_y;
+        let tan_theta_squared = (x * x + y * y) / cos_theta_squared;
+        (-tan_theta_squared).exp()
+            / (PI * self.alpha_x * self.alpha_y * cos_theta_squared * cos_theta_squared)
+    }
+
+    fn lambda(&self, direction: Vec3) -> f32 {
+        let x = self.alpha_x * direction.x;
+        let y = self.alpha_y * direction.y;
+        let projected = (x * x + y * y).sqrt();
+        if projected == 0.0 {
+            return 0.0;
+        }
+
+        // Rational approximation by Walter et al.
+        let a = direction.z.abs() / projected;
+        if a >= 1.6 {
+            return 0.0;
+        }
+
+        (1.0 - 1.259 * a + 0.396 * a * a) / (3.535 * a + 2.181 * a * a)
+    }
+
+    fn sample_normal(&self, _outgoing: Vec3, u: f32, v: f32) -> Vec3 {
+        let mut phi = (self.alpha_y / self.alpha_x * (2.0 * PI * v + 0.5 * PI).tan()).atan();
+        if v > 0.5 {
+            phi += PI;
+        }
+
+        let (sin_phi, cos_phi) = phi.sin_cos();
+        let alpha_squared = 1.0
+            / (cos_phi * cos_phi / (self.alpha_x * self.alpha_x)
+                + sin_phi * sin_phi / (self.alpha_y * self.alpha_y));
+        let tan_theta_squared = -alpha_squared * (1.0 - u).ln();
+        let cos_theta = 1.0 / (1.0 + tan_theta_squared).sqrt();
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+
+        Vec3::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta)
+    }
+
+    fn pdf(&self, _outgoing: Vec3, normal: Vec3) -> f32 {
+        self.distribution(normal) * normal.z
+    }
+}
@@ -0,0 +1,56 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::math::Vec3;
+
+use cgmath::InnerSpace;
+
+/// Orthonormal basis built around a single direction, used to place locally sampled
+/// directions into world space.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Onb {
+    u: Vec3,
+    v: Vec3,
+    w: Vec3,
+}
+
+impl Onb {
+    pub(crate) fn from_w(w: Vec3) -> Self {
+        let w = w.normalize();
+        let a = if w.x.abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = w.cross(a).normalize();
+        let u = w.cross(v);
+
+        Self { u, v, w }
+    }
+
+    /// Basis around `w` with `u` along the part of the given tangent perpendicular to it.
+    pub(crate) fn from_wu(w: Vec3, u: Vec3) -> Self {
+        let w = w.normalize();
+        let u = u - w * w.dot(u);
+        if u.magnitude2() < 1e-12 {
+            return Self::from_w(w);
+        }
+
+        let u = u.normalize();
+        let v = w.cross(u);
+
+        Self { u, v, w }
+    }
+
+    pub(crate) fn local(&self, a: Vec3) -> Vec3 {
+        a.x * self.u + a.y * self.v + a.z * self.w
+    }
+
+    /// Expresses a world space direction in the coordinates of the basis.
+    pub(crate) fn world_to_local(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
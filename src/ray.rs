@@ -11,6 +11,7 @@ pub(crate) struct Ray {
     origin: Vec3,
     direction: Vec3,
     time: f32,
+    wavelength: Option<f32>,
 }
 
 impl Ray {
@@ -19,9 +20,14 @@ impl Ray {
             origin,
             direction,
             time,
+            wavelength: None,
         }
     }
 
+    pub(crate) fn with_wavelength(self, wavelength: Option<f32>) -> Self {
+        Self { wavelength, ..self }
+    }
+
     pub(crate) fn at(&self, t: f32) -> Vec3 {
         self.origin + t * self.direction
     }
@@ -37,4 +43,9 @@ impl Ray {
     pub(crate) fn time(&self) -> f32 {
         self.time
     }
+
+    /// Wavelength in nanometers the ray carries in spectral mode.
+    pub(crate) fn wavelength(&self) -> Option<f32> {
+        self.wavelength
+    }
 }
@@ -0,0 +1,157 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use std::{cell::RefCell, f32::consts::PI};
+
+thread_local! {
+    static PRIMARY_SAMPLE_SPACE: RefCell<Option<PrimarySampleSpace>> = const { RefCell::new(None) };
+}
+
+/// Source of every random number consumed while rendering. It forwards to the thread's
+/// generator unless a primary sample space is active on the current thread.
+pub(crate) struct Sampler;
+
+pub(crate) fn rng() -> Sampler {
+    Sampler
+}
+
+impl RngCore for Sampler {
+    fn next_u32(&mut self) -> u32 {
+        PRIMARY_SAMPLE_SPACE.with(|space| match space.borrow_mut().as_mut() {
+            Some(space) => (space.next() as f64 * 4294967296.0) as u32,
+            None => rand::thread_rng().next_u32(),
+        })
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(4) {
+            let bytes = self.next_u32().to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+/// Runs `f` with all random numbers taken from the given primary sample space.
+pub(crate) fn with_primary_samples<R>(
+    space: PrimarySampleSpace,
+    f: impl FnOnce() -> R,
+) -> (PrimarySampleSpace, R) {
+    PRIMARY_SAMPLE_SPACE.with(|cell| *cell.borrow_mut() = Some(space));
+    let result = f();
+    let space = PRIMARY_SAMPLE_SPACE.with(|cell| cell.borrow_mut().take().unwrap());
+    (space, result)
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct PrimarySample {
+    value: f32,
+    last_modification: u64,
+    value_backup: f32,
+    modification_backup: u64,
+}
+
+/// Vector of uniform random numbers which is lazily mutated by either replacing every
+/// coordinate (large step) or perturbing each of them slightly (small step).
+pub(crate) struct PrimarySampleSpace {
+    rand: StdRng,
+    samples: Vec<PrimarySample>,
+    large_step_probability: f32,
+    small_step_sigma: f32,
+    iteration: u64,
+    large_step: bool,
+    last_large_step_iteration: u64,
+    index: usize,
+}
+
+impl PrimarySampleSpace {
+    pub(crate) fn new(seed: u64, large_step_probability: f32, small_step_sigma: f32) -> Self {
+        Self {
+            rand: StdRng::seed_from_u64(seed),
+            samples: Vec::new(),
+            large_step_probability,
+            small_step_sigma,
+            iteration: 0,
+            large_step: true,
+            last_large_step_iteration: 0,
+            index: 0,
+        }
+    }
+
+    /// Replaces the generator driving future mutations, keeping the current samples.
+    pub(crate) fn reseed(&mut self, seed: u64) {
+        self.rand = StdRng::seed_from_u64(seed);
+    }
+
+    pub(crate) fn start_iteration(&mut self) {
+        self.iteration += 1;
+        self.large_step = self.rand.gen::<f32>() < self.large_step_probability;
+        self.index = 0;
+    }
+
+    pub(crate) fn accept(&mut self) {
+        if self.large_step {
+            self.last_large_step_iteration = self.iteration;
+        }
+    }
+
+    pub(crate) fn reject(&mut self) {
+        for sample in &mut self.samples {
+            if sample.last_modification == self.iteration {
+                sample.value = sample.value_backup;
+                sample.last_modification = sample.modification_backup;
+            }
+        }
+
+        self.iteration -= 1;
+    }
+
+    fn next(&mut self) -> f32 {
+        if self.index >= self.samples.len() {
+            self.samples
+                .resize(self.index + 1, PrimarySample::default());
+        }
+
+        let sample = &mut self.samples[self.index];
+        self.index += 1;
+
+        // Catch up with the last accepted large step the sample wasn't used in
+        if sample.last_modification < self.last_large_step_iteration {
+            sample.value = self.rand.gen();
+            sample.last_modification = self.last_large_step_iteration;
+        }
+
+        sample.value_backup = sample.value;
+        sample.modification_backup = sample.last_modification;
+
+        if self.large_step {
+            sample.value = self.rand.gen();
+        } else {
+            // Every skipped small step widens the perturbation
+            let small_steps = (self.iteration - sample.last_modification) as f32;
+            let sigma = self.small_step_sigma * small_steps.sqrt();
+
+            let radius = (-2.0 * (1.0 - self.rand.gen::<f32>()).ln()).sqrt();
+            let normal = radius * (2.0 * PI * self.rand.gen::<f32>()).cos();
+
+            sample.value += normal * sigma;
+            sample.value -= sample.value.floor();
+            sample.value = sample.value.min(1.0 - f32::EPSILON);
+        }
+
+        sample.last_modification = self.iteration;
+        sample.value
+    }
+}
@@ -0,0 +1,224 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{materials::ScatterRecord, math::Vec3};
+
+use cgmath::{ElementWise, Matrix3, Vector4};
+use std::{ops::Add, sync::OnceLock};
+
+pub(crate) const WAVELENGTH_MIN: f32 = 380.0;
+pub(crate) const WAVELENGTH_MAX: f32 = 720.0;
+
+const WAVELENGTH_COUNT: usize = 4;
+
+/// Radiance at each of the sampled wavelengths.
+pub(crate) type Spectrum = Vector4<f32>;
+
+/// How radiance is represented while it travels along a path.
+pub(crate) trait ColorSpace {
+    type Radiance: Copy + Add<Output = Self::Radiance> + ElementWise;
+
+    fn radiance(&self, rgb: Vec3) -> Self::Radiance;
+
+    /// Wavelength in nanometers used by wavelength dependent materials.
+    fn wavelength(&self) -> Option<f32>;
+
+    fn scattered(&mut self, scatter_record: &ScatterRecord);
+}
+
+pub(crate) struct Rgb;
+
+impl ColorSpace for Rgb {
+    type Radiance = Vec3;
+
+    fn radiance(&self, rgb: Vec3) -> Vec3 {
+        rgb
+    }
+
+    fn wavelength(&self) -> Option<f32> {
+        None
+    }
+
+    fn scattered(&mut self, _scatter_record: &ScatterRecord) {}
+}
+
+/// Hero wavelength sampling, the first wavelength is sampled uniformly and the others are
+/// spread evenly over the visible range from it.
+pub(crate) struct SampledWavelengths {
+    wavelengths: [f32; WAVELENGTH_COUNT],
+    pdfs: [f32; WAVELENGTH_COUNT],
+}
+
+impl SampledWavelengths {
+    pub(crate) fn sample(u: f32) -> Self {
+        let range = WAVELENGTH_MAX - WAVELENGTH_MIN;
+
+        let mut wavelengths = [0.0; WAVELENGTH_COUNT];
+        for (i, wavelength) in wavelengths.iter_mut().enumerate() {
+            let offset = (u + i as f32 / WAVELENGTH_COUNT as f32).fract();
+            *wavelength = WAVELENGTH_MIN + offset * range;
+        }
+
+        Self {
+            wavelengths,
+            pdfs: [1.0 / range; WAVELENGTH_COUNT],
+        }
+    }
+
+    /// Keeps only the hero wavelength, used once the path depends on the wavelength.
+    fn terminate_secondary(&mut self) {
+        if self.pdfs[1] == 0.0 {
+            return;
+        }
+
+        self.pdfs[0] /= WAVELENGTH_COUNT as f32;
+        self.pdfs[1..].iter_mut().for_each(|pdf| *pdf = 0.0);
+    }
+
+    pub(crate) fn to_rgb(&self, spectrum: Spectrum) -> Vec3 {
+        let mut xyz = Vec3::new(0.0, 0.0, 0.0);
+        for i in 0..WAVELENGTH_COUNT {
+            if self.pdfs[i] == 0.0 {
+                continue;
+            }
+
+            xyz += color_matching(self.wavelengths[i]) * (spectrum[i] / self.pdfs[i]);
+        }
+
+        xyz_to_srgb(xyz / WAVELENGTH_COUNT as f32).div_element_wise(white())
+    }
+}
+
+impl ColorSpace for SampledWavelengths {
+    type Radiance = Spectrum;
+
+    fn radiance(&self, rgb: Vec3) -> Spectrum {
+        Spectrum::new(
+            rgb_to_spectrum(rgb, self.wavelengths[0]),
+            rgb_to_spectrum(rgb, self.wavelengths[1]),
+            rgb_to_spectrum(rgb, self.wavelengths[2]),
+            rgb_to_spectrum(rgb, self.wavelengths[3]),
+        )
+    }
+
+    fn wavelength(&self) -> Option<f32> {
+        Some(self.wavelengths[0])
+    }
+
+    fn scattered(&mut self, scatter_record: &ScatterRecord) {
+        if scatter_record.dispersive {
+            self.terminate_secondary();
+        }
+    }
+}
+
+// Smits' basis spectra for RGB upsampling in ten bins between 380nm and 720nm
+const SMITS_WHITE: [f32; 10] = [
+    1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000,
+];
+const SMITS_CYAN: [f32; 10] = [
+    0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000,
+];
+const SMITS_MAGENTA: [f32; 10] = [
+    1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959,
+];
+const SMITS_YELLOW: [f32; 10] = [
+    0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840,
+];
+const SMITS_RED: [f32; 10] = [
+    0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149,
+];
+const SMITS_GREEN: [f32; 10] = [
+    0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025,
+];
+const SMITS_BLUE: [f32; 10] = [
+    1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496,
+];
+
+/// Upsamples an RGB color to its spectral value at `wavelength` following Smits.
+pub(crate) fn rgb_to_spectrum(rgb: Vec3, wavelength: f32) -> f32 {
+    let bin = ((wavelength - WAVELENGTH_MIN) / (WAVELENGTH_MAX - WAVELENGTH_MIN) * 10.0)
+        .clamp(0.0, 9.0) as usize;
+    let (r, g, b) = (rgb.x, rgb.y, rgb.z);
+
+    if r <= g && r <= b {
+        let base = r * SMITS_WHITE[bin];
+        if g <= b {
+            base + (g - r) * SMITS_CYAN[bin] + (b - g) * SMITS_BLUE[bin]
+        } else {
+            base + (b - r) * SMITS_CYAN[bin] + (g - b) * SMITS_GREEN[bin]
+        }
+    } else if g <= r && g <= b {
+        let base = g * SMITS_WHITE[bin];
+        if r <= b {
+            base + (r - g) * SMITS_MAGENTA[bin] + (b - r) * SMITS_BLUE[bin]
+        } else {
+            base + (b - g) * SMITS_MAGENTA[bin] + (r - b) * SMITS_RED[bin]
+        }
+    } else {
+        let base = b * SMITS_WHITE[bin];
+        if r <= g {
+            base + (r - b) * SMITS_YELLOW[bin] + (g - r) * SMITS_GREEN[bin]
+        } else {
+            base + (g - b) * SMITS_YELLOW[bin] + (r - g) * SMITS_RED[bin]
+        }
+    }
+}
+
+/// CIE 1931 color matching functions using the multi-lobe fit by Wyman, Sloan and Shirley.
+pub(crate) fn color_matching(wavelength: f32) -> Vec3 {
+    let lobe = |mean: f32, lower: f32, upper: f32| {
+        let deviation = if wavelength < mean { lower } else { upper };
+        let t = (wavelength - mean) / deviation;
+        (-0.5 * t * t).exp()
+    };
+
+    Vec3::new(
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+pub(crate) fn xyz_to_srgb(xyz: Vec3) -> Vec3 {
+    // Columns of the XYZ to linear sRGB matrix
+    let matrix = Matrix3::new(
+        3.240_454, -0.969_266, 0.055_643, -1.537_139, 1.876_011, -0.204_026, -0.498_531, 0.041_556,
+        1.057_225,
+    );
+    matrix * xyz
+}
+
+/// Linear sRGB of a constant spectrum, dividing by it maps a white spectrum to white.
+fn white() -> Vec3 {
+    static WHITE: OnceLock<Vec3> = OnceLock::new();
+    *WHITE.get_or_init(|| {
+        let steps = (WAVELENGTH_MAX - WAVELENGTH_MIN) as u32;
+        let xyz = (0..steps)
+            .map(|step| color_matching(WAVELENGTH_MIN + step as f32 + 0.5))
+            .fold(Vec3::new(0.0, 0.0, 0.0), |sum, value| sum + value);
+        xyz_to_srgb(xyz)
+    })
+}
+
+/// Linear sRGB color of a reflectance spectrum, used to render wavelength dependent
+/// materials without spectral mode.
+pub(crate) fn reflectance_to_rgb(reflectance: impl Fn(f32) -> f32) -> Vec3 {
+    const STEPS: u32 = 32;
+
+    let step = (WAVELENGTH_MAX - WAVELENGTH_MIN) / STEPS as f32;
+    let xyz = (0..STEPS)
+        .map(|i| {
+            let wavelength = WAVELENGTH_MIN + (i as f32 + 0.5) * step;
+            color_matching(wavelength) * reflectance(wavelength)
+        })
+        .fold(Vec3::new(0.0, 0.0, 0.0), |sum, value| sum + value);
+
+    // The white normalization is integrated in steps of one nanometer
+    let rgb = xyz_to_srgb(xyz * step).div_element_wise(white());
+    Vec3::new(rgb.x.max(0.0), rgb.y.max(0.0), rgb.z.max(0.0))
+}
@@ -0,0 +1,72 @@
+/*
+ * Copyright (c) 2023, SkillerRaptor
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+use crate::{
+    hittable::Hittable,
+    lights::{light_sampler::LightSampler, Light},
+    math::Vec3,
+    ray::Ray,
+};
+
+pub(crate) struct World {
+    objects: Box<dyn Hittable>,
+    lights: Vec<Box<dyn Light>>,
+    light_sampler: LightSampler,
+    background: Vec3,
+}
+
+impl World {
+    pub(crate) fn new(
+        objects: Box<dyn Hittable>,
+        mut lights: Vec<Box<dyn Light>>,
+        background: Vec3,
+    ) -> Self {
+        if let Some(bounds) = objects.bounding_box(0.0, 1.0) {
+            lights
+                .iter_mut()
+                .for_each(|light| light.preprocess(&bounds));
+        }
+
+        lights
+            .iter()
+            .enumerate()
+            .for_each(|(index, light)| light.register(index));
+        let light_sampler = LightSampler::new(&lights);
+
+        Self {
+            objects,
+            lights,
+            light_sampler,
+            background,
+        }
+    }
+
+    pub(crate) fn objects(&self) -> &dyn Hittable {
+        &*self.objects
+    }
+
+    pub(crate) fn lights(&self) -> &[Box<dyn Light>] {
+        &self.lights
+    }
+
+    pub(crate) fn light_sampler(&self) -> &LightSampler {
+        &self.light_sampler
+    }
+
+    pub(crate) fn background(&self) -> Vec3 {
+        self.background
+    }
+
+    /// Radiance of lights at infinity seen by a ray escaping the scene, on top of the
+    /// constant background.
+    pub(crate) fn environment(&self, ray: &Ray) -> Vec3 {
+        self.lights
+            .iter()
+            .fold(Vec3::new(0.0, 0.0, 0.0), |radiance, light| {
+                radiance + light.environment(ray.direction())
+            })
+    }
+}